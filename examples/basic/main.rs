@@ -37,7 +37,7 @@ async fn handler(State(app): State<AppState>) -> impl IntoResponse {
         parts.insert("number1", 1);
         parts.insert("number2", 2);
 
-        return route.with(parts).redirect(Html("")); // we are creating a response with an empty HTML body
+        route.with(parts).redirect(Html("")) // we are creating a response with an empty HTML body
     } else {
         Html("<h1>We could not get the route named <b>add_numbers</b></h1>").into_response()
     }
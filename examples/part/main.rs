@@ -33,7 +33,7 @@ async fn handler(State(app): State<AppState>) -> impl IntoResponse {
         // these are the values.
         let part = rand::rng().random_range(0..6);
 
-        return route.with(part.to_string()).redirect(()); // we are creating a response with an empty body
+        route.with(part.to_string()).redirect(()) // we are creating a response with an empty body
     } else {
         Html("<h1>We could not get the route named <b>add_numbers</b></h1>").into_response()
     }
@@ -0,0 +1,45 @@
+use axum::response::IntoResponse;
+use named_routes_axum::{RouteRegistry, RouterWrapper};
+
+#[tokio::main]
+async fn main() {
+    // 1. Each application gets its own registry so that route names do not clash
+    let public_registry = RouteRegistry::new();
+    let admin_registry = RouteRegistry::new();
+
+    let public =
+        RouterWrapper::with_registry(public_registry.clone()).get("/", public_home, "home");
+    let admin = RouterWrapper::with_registry(admin_registry.clone()).get("/", admin_home, "home");
+
+    // 2. Resolve names against the registry of each application
+    println!(
+        "public home: {:?}, admin home: {:?}",
+        public_registry.service().get_path("home"),
+        admin_registry.service().get_path("home")
+    );
+
+    let public_listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
+        .await
+        .unwrap();
+    let admin_listener = tokio::net::TcpListener::bind("127.0.0.1:3001")
+        .await
+        .unwrap();
+
+    println!("listening on {}", public_listener.local_addr().unwrap());
+    println!("listening on {}", admin_listener.local_addr().unwrap());
+
+    let (public_result, admin_result) = tokio::join!(
+        axum::serve(public_listener, public.into_router()),
+        axum::serve(admin_listener, admin.into_router())
+    );
+    public_result.unwrap();
+    admin_result.unwrap();
+}
+
+async fn public_home() -> impl IntoResponse {
+    "Welcome"
+}
+
+async fn admin_home() -> impl IntoResponse {
+    "Welcome to the admin"
+}
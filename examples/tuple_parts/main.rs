@@ -35,7 +35,7 @@ async fn handler(State(app): State<AppState>) -> impl IntoResponse {
         // In this case 1 is assign to number1 and 2 to number2
         let parts = (1, 2);
 
-        return route.with(parts).redirect(Html("")); // we are creating a response with an empty HTML body
+        route.with(parts).redirect(Html("")) // we are creating a response with an empty HTML body
     } else {
         Html("<h1>We could not get the route named <b>add_numbers</b></h1>").into_response()
     }
@@ -0,0 +1,180 @@
+use std::{fmt::Display, str::FromStr};
+
+use axum::http::Uri;
+
+use crate::NamedRoutesError;
+
+/// The scheme, host, port and optional path prefix used to build absolute URLs
+///
+/// ```rust
+///  use named_routes_axum::BaseUrl;
+///
+/// let base: BaseUrl = "https://example.com:8443/app/".parse().unwrap();
+///
+/// assert_eq!(base.to_string(), "https://example.com:8443/app");
+/// assert_eq!(base, BaseUrl::new("https", "example.com").port(8443).prefix("/app"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseUrl {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+    prefix: String,
+}
+
+impl BaseUrl {
+    pub fn new(scheme: &str, host: &str) -> Self {
+        Self {
+            scheme: scheme.to_ascii_lowercase(),
+            host: host.to_string(),
+            port: None,
+            prefix: String::new(),
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets the path that is prepended to every generated path, e.g. `/app`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        let prefix = prefix.trim_matches('/');
+        self.prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", prefix)
+        };
+        self
+    }
+
+    pub fn scheme_str(&self) -> &str {
+        &self.scheme
+    }
+
+    pub fn host_str(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port_u16(&self) -> Option<u16> {
+        self.port
+    }
+
+    pub fn prefix_str(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Joins the base with a path that starts with `/`
+    pub fn join(&self, path: &str) -> Result<Uri, NamedRoutesError> {
+        let url = format!("{}{}", self, path);
+        url.parse::<Uri>()
+            .map_err(|_| NamedRoutesError::InvalidUrl(url))
+    }
+}
+
+impl BaseUrl {
+    /// Returns true when the URL has the same scheme, host and port
+    pub(crate) fn same_origin(&self, uri: &Uri) -> bool {
+        let (Some(scheme), Some(host)) = (uri.scheme_str(), uri.host()) else {
+            return false;
+        };
+
+        scheme.eq_ignore_ascii_case(&self.scheme)
+            && host.eq_ignore_ascii_case(&self.host)
+            && uri.port_u16().or_else(|| default_port(scheme))
+                == self.port.or_else(|| default_port(&self.scheme))
+    }
+
+    /// Removes the path prefix, returns `None` when the path is outside of it
+    pub(crate) fn strip_prefix<'a>(&self, path: &'a str) -> Option<&'a str> {
+        match path.strip_prefix(self.prefix.as_str()) {
+            Some("") => Some("/"),
+            Some(rest) if rest.starts_with('/') => Some(rest),
+            _ => None,
+        }
+    }
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme.to_ascii_lowercase().as_str() {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    }
+}
+
+impl Display for BaseUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}", self.scheme, self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        write!(f, "{}", self.prefix)
+    }
+}
+
+impl FromStr for BaseUrl {
+    type Err = NamedRoutesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || NamedRoutesError::InvalidUrl(s.to_string());
+        let uri = s.parse::<Uri>().map_err(|_| error())?;
+
+        let scheme = uri.scheme_str().ok_or_else(error)?;
+        let host = uri.host().ok_or_else(error)?;
+        if uri.query().is_some() {
+            return Err(error());
+        }
+
+        let mut base = Self::new(scheme, host).prefix(uri.path());
+        if let Some(port) = uri.port_u16() {
+            base = base.port(port);
+        }
+
+        Ok(base)
+    }
+}
+
+impl TryFrom<&str> for BaseUrl {
+    type Error = NamedRoutesError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BaseUrl;
+    use crate::NamedRoutesError;
+
+    #[test]
+    fn test_parsing_base_url() {
+        let base: BaseUrl = "http://localhost:3000".parse().unwrap();
+        assert_eq!(base, BaseUrl::new("http", "localhost").port(3000));
+        assert_eq!(base.prefix_str(), "");
+
+        let base: BaseUrl = "https://example.com/app/v1/".parse().unwrap();
+        assert_eq!(base.prefix_str(), "/app/v1");
+        assert_eq!(base.to_string(), "https://example.com/app/v1");
+    }
+
+    #[test]
+    fn test_invalid_base_url() {
+        assert_eq!(
+            "/app".parse::<BaseUrl>(),
+            Err(NamedRoutesError::InvalidUrl("/app".to_string()))
+        );
+        assert!("https://example.com/?a=b".parse::<BaseUrl>().is_err());
+    }
+
+    #[test]
+    fn test_joining_a_path() {
+        let base = BaseUrl::new("https", "example.com").prefix("app");
+
+        assert_eq!(
+            base.join("/users/1?tab=posts").unwrap().to_string(),
+            "https://example.com/app/users/1?tab=posts"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue, Response},
+};
+
+use crate::{
+    NamedRoutesError, NamedRoutesService, PartsValue, RedirectKind, Redirector, RouteRegistry,
+};
+
+/// The kind of client that sent the request
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectClient {
+    /// A regular browser navigation, sent a standard redirect
+    #[default]
+    Browser,
+    /// An htmx request (`HX-Request`), sent `HX-Redirect` or `HX-Location`
+    Htmx,
+    /// A Hotwire Turbo request, sent see other/303
+    Turbo,
+}
+
+impl RedirectClient {
+    /// Detects the client from the request headers
+    pub fn detect(headers: &HeaderMap) -> Self {
+        if headers.contains_key("hx-request") {
+            Self::Htmx
+        } else if headers.contains_key("turbo-frame")
+            || headers
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.contains("text/vnd.turbo-stream.html"))
+        {
+            Self::Turbo
+        } else {
+            Self::Browser
+        }
+    }
+}
+
+/// Redirects that work for htmx and Turbo requests as well as for browsers.
+///
+/// A 302 sent to an htmx request is followed inside the XHR, so htmx requests
+/// get a 200 with `HX-Redirect`, or with `HX-Location` when a target or swap is set.
+/// Turbo requests get see other/303 and every other request gets the redirector's kind.
+///
+/// ```rust
+///  use axum::response::Response;
+///  use named_routes_axum::{ClientRedirect, NamedRoutesError};
+///
+/// async fn handler(redirect: ClientRedirect) -> Result<Response, NamedRoutesError> {
+///     redirect.target("#main").redirect("home")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientRedirect {
+    client: RedirectClient,
+    service: NamedRoutesService,
+    target: Option<String>,
+    swap: Option<String>,
+}
+
+impl ClientRedirect {
+    pub fn new(client: RedirectClient, service: NamedRoutesService) -> Self {
+        Self {
+            client,
+            service,
+            target: None,
+            swap: None,
+        }
+    }
+
+    /// Builds the redirect from a request's parts
+    pub fn from_parts(parts: &Parts) -> Self {
+        let registry = RouteRegistry::from_parts(parts);
+
+        Self::new(RedirectClient::detect(&parts.headers), registry.service())
+    }
+
+    pub fn client(&self) -> RedirectClient {
+        self.client
+    }
+
+    /// The element htmx swaps the new page into. Makes htmx requests use `HX-Location`
+    pub fn target(mut self, selector: &str) -> Self {
+        self.target = Some(selector.to_string());
+        self
+    }
+
+    /// How htmx swaps the new page, e.g. `innerHTML`. Makes htmx requests use `HX-Location`
+    pub fn swap(mut self, swap: &str) -> Self {
+        self.swap = Some(swap.to_string());
+        self
+    }
+
+    pub fn redirect(&self, name: &str) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirect_with(name, ())
+    }
+
+    pub fn redirect_with<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirect_to(&self.service.try_get(name)?.try_with(parts)?)
+    }
+
+    /// Redirects to the redirector's location, e.g. `redirect.redirect_to(&route.redirector())`
+    pub fn redirect_to(&self, redirector: &Redirector) -> Result<Response<Body>, NamedRoutesError> {
+        match self.client {
+            RedirectClient::Browser => redirector.try_redirect(()),
+            RedirectClient::Turbo => redirector.try_redirect_as(RedirectKind::SeeOther, ()),
+            RedirectClient::Htmx => {
+                redirector.check_follow_method(RedirectKind::SeeOther)?;
+                let location = redirector.location()?;
+                let mut response = Response::new(Body::empty());
+                redirector.append_flash(response.headers_mut())?;
+
+                if self.target.is_none() && self.swap.is_none() {
+                    response.headers_mut().insert("hx-redirect", location);
+                } else {
+                    let value = self.hx_location(location.to_str().unwrap_or_default());
+                    let value = HeaderValue::from_str(&value)
+                        .map_err(|_| NamedRoutesError::InvalidHeaderValue(value))?;
+                    response.headers_mut().insert("hx-location", value);
+                }
+
+                Ok(response)
+            }
+        }
+    }
+
+    /// The JSON value of the `HX-Location` header
+    fn hx_location(&self, path: &str) -> String {
+        let mut value = format!("{{\"path\":{}", json_string(path));
+        if let Some(target) = &self.target {
+            value.push_str(&format!(",\"target\":{}", json_string(target)));
+        }
+        if let Some(swap) = &self.swap {
+            value.push_str(&format!(",\"swap\":{}", json_string(swap)));
+        }
+        value.push('}');
+        value
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientRedirect {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts))
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        response::Response,
+    };
+    use tower::ServiceExt;
+
+    use super::{ClientRedirect, RedirectClient};
+    use crate::{
+        test_support::{location, registry_with, request_parts},
+        NamedRoutesError, RouteRegistry, RouterWrapper,
+    };
+
+    fn redirect(headers: &[(&str, &str)]) -> ClientRedirect {
+        let registry = registry_with(&[("user", "/user/{id}")]);
+        ClientRedirect::from_parts(&request_parts(&registry, "/", headers))
+    }
+
+    fn headers(headers: &[(&str, &str)]) -> axum::http::HeaderMap {
+        request_parts(&RouteRegistry::new(), "/", headers).headers
+    }
+
+    #[test]
+    fn test_detect_client() {
+        assert_eq!(
+            RedirectClient::detect(&headers(&[])),
+            RedirectClient::Browser
+        );
+        assert_eq!(
+            RedirectClient::detect(&headers(&[("hx-request", "true")])),
+            RedirectClient::Htmx
+        );
+        assert_eq!(
+            RedirectClient::detect(&headers(&[(
+                "accept",
+                "text/vnd.turbo-stream.html, text/html, application/xhtml+xml"
+            )])),
+            RedirectClient::Turbo
+        );
+    }
+
+    #[test]
+    fn test_redirect_per_client() {
+        let response = redirect(&[]).redirect_with("user", (1,)).unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(location(&response), "/user/1");
+
+        let response = redirect(&[("turbo-frame", "main")])
+            .redirect_with("user", (1,))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let response = redirect(&[("hx-request", "true")])
+            .redirect_with("user", (1,))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("hx-redirect").unwrap(), "/user/1");
+        assert!(response.headers().get(header::LOCATION).is_none());
+
+        let response = redirect(&[("hx-request", "true")])
+            .target("#main")
+            .swap("innerHTML")
+            .redirect_with("user", (1,))
+            .unwrap();
+        assert_eq!(
+            response.headers().get("hx-location").unwrap(),
+            r##"{"path":"/user/1","target":"#main","swap":"innerHTML"}"##
+        );
+    }
+
+    async fn save(redirect: ClientRedirect) -> Result<Response, NamedRoutesError> {
+        redirect.redirect("home")
+    }
+
+    #[tokio::test]
+    async fn test_extractor() {
+        let registry = RouteRegistry::new();
+        let app = RouterWrapper::<()>::with_registry(registry)
+            .get("/", || async { "home" }, "home")
+            .post("/save", save, "save")
+            .into_router();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/save")
+                    .header("hx-request", "true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers().get("hx-redirect").unwrap(), "/");
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{FromRequestParts, MatchedPath, RawPathParams},
+    http::request::Parts,
+};
+
+use crate::{NamedRoutesError, RouteRegistry};
+
+/// The name given to a route by `RouterWrapper::name_route`, added to its requests
+#[derive(Debug, Clone)]
+pub(crate) struct RouteName(pub(crate) String);
+
+/// The named route that is serving the request.
+///
+/// Each route registered through `RouterWrapper` is tagged with its name, also for
+/// the layers added with `RouterWrapper::route_layer` and `middleware`. When the
+/// tag is not there, e.g. in a middleware added with `Router::route_layer`, the
+/// name is looked up with axum's `MatchedPath`.
+///
+/// ```rust
+///  use named_routes_axum::CurrentRoute;
+///
+/// async fn handler(route: CurrentRoute) -> String {
+///     format!("{} {}", route.name, route.template)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentRoute {
+    /// The name of the route
+    pub name: String,
+    /// The path the route was registered with, e.g. `/users/{id}`
+    pub template: String,
+    /// The percent-decoded parameter values, keyed by parameter name
+    pub params: HashMap<String, String>,
+}
+
+impl CurrentRoute {
+    /// Returns the value of a parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for CurrentRoute {
+    type Rejection = NamedRoutesError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let registry = RouteRegistry::from_parts(parts);
+
+        let name = match parts.extensions.get::<RouteName>() {
+            Some(name) => name.0.clone(),
+            None => parts
+                .extensions
+                .get::<MatchedPath>()
+                .and_then(|matched| registry.name_of(matched.as_str(), &parts.method))
+                .ok_or_else(|| NamedRoutesError::UnnamedRoute(parts.uri.path().to_string()))?,
+        };
+        let template = registry.service().try_get(&name)?.template().to_string();
+
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            name,
+            template,
+            params,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::StatusCode,
+        middleware::{from_fn, Next},
+        response::Response,
+    };
+    use tower::ServiceExt;
+
+    use super::CurrentRoute;
+    use crate::{test_support::body_string, RouteRegistry, RouterWrapper};
+
+    async fn show(route: CurrentRoute) -> String {
+        format!(
+            "{} {} {}",
+            route.name,
+            route.template,
+            route.param("id").unwrap_or_default()
+        )
+    }
+
+    fn request(method: &str, uri: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_current_route_in_handlers() {
+        let app = RouterWrapper::<()>::with_registry(RouteRegistry::new())
+            .get("/users/{id}", show, "users.show")
+            .put("/users/{id}", show, "users.update")
+            .nest_given("/admin", |router| router.get("/{id}", show, "admin.show"))
+            .into_router();
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/users/a%20b"))
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await, "users.show /users/{id} a b");
+
+        let response = app
+            .clone()
+            .oneshot(request("PUT", "/users/1"))
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await, "users.update /users/{id} 1");
+
+        let response = app.oneshot(request("GET", "/admin/7")).await.unwrap();
+        assert_eq!(body_string(response).await, "admin.show /admin/{id} 7");
+    }
+
+    #[tokio::test]
+    async fn test_current_route_in_route_layer() {
+        async fn tag(route: CurrentRoute, request: Request, next: Next) -> Response {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert("x-route", route.name.parse().unwrap());
+            response
+        }
+
+        let app = RouterWrapper::<()>::new()
+            .get("/current-route/{id}", || async { "ok" }, "current-route")
+            .into_router()
+            .route_layer(from_fn(tag));
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/current-route/1"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-route"], "current-route");
+
+        let response = app.oneshot(request("GET", "/missing")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_current_route_in_wrapper_route_layers() {
+        async fn tag(route: CurrentRoute, request: Request, next: Next) -> Response {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert("x-route", route.name.parse().unwrap());
+            response
+        }
+
+        let app = RouterWrapper::<()>::with_registry(RouteRegistry::new())
+            .get("/users/{id}", show, "users.show")
+            .route_layer(from_fn(tag))
+            .into_router();
+
+        let response = app.oneshot(request("GET", "/users/1")).await.unwrap();
+        assert_eq!(response.headers()["x-route"], "users.show");
+        assert_eq!(body_string(response).await, "users.show /users/{id} 1");
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Characters that must be encoded in a path segment.
+/// RFC 3986 allows unreserved characters, sub-delims, `:` and `@`
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b'+')
+    .remove(b',')
+    .remove(b';')
+    .remove(b'=')
+    .remove(b':')
+    .remove(b'@');
+
+/// Percent-encodes a value so that it can be used as a single path segment
+pub(crate) fn write_path_segment<W: fmt::Write>(out: &mut W, value: &str) -> fmt::Result {
+    write!(out, "{}", utf8_percent_encode(value, PATH_SEGMENT))
+}
+
+/// Percent-encodes a catch-all value. Each `/` separated segment is encoded on its own
+pub(crate) fn write_path_segments<W: fmt::Write>(out: &mut W, value: &str) -> fmt::Result {
+    for (pos, segment) in value.split('/').enumerate() {
+        if pos > 0 {
+            out.write_char('/')?;
+        }
+        write_path_segment(out, segment)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{write_path_segment, write_path_segments};
+
+    fn encode_path_segment(value: &str) -> String {
+        let mut out = String::new();
+        write_path_segment(&mut out, value).unwrap();
+        out
+    }
+
+    fn encode_path_segments(value: &str) -> String {
+        let mut out = String::new();
+        write_path_segments(&mut out, value).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("hello"), "hello");
+        assert_eq!(encode_path_segment("a b/c?d"), "a%20b%2Fc%3Fd");
+        assert_eq!(encode_path_segment("50%#"), "50%25%23");
+        assert_eq!(encode_path_segment("user@host:1"), "user@host:1");
+        assert_eq!(encode_path_segment("é"), "%C3%A9");
+    }
+
+    #[test]
+    fn test_encode_path_segments() {
+        assert_eq!(encode_path_segments("docs/a b.txt"), "docs/a%20b.txt");
+        assert_eq!(encode_path_segments("a?b/c#d"), "a%3Fb/c%23d");
+    }
+}
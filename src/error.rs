@@ -0,0 +1,96 @@
+use std::{error::Error, fmt::Display};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// Errors returned by the fallible lookup and URL building functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamedRoutesError {
+    /// No route has been registered with this name
+    UnknownRoute(String),
+    /// The route's path requires a parameter that was not provided
+    MissingParameter { route: String, parameter: String },
+    /// A parameter was provided that the route's path does not have
+    ExtraParameter { route: String, parameter: String },
+    /// A parameter was provided with an empty value
+    EmptyParameter { route: String, parameter: String },
+    /// A positional value was left over because its parameter was given by name
+    ConflictingParameter { route: String, parameter: String },
+    /// The route is not registered for the method, e.g. a redirect to a POST only route
+    MethodNotAllowed { route: String, method: String },
+    /// The request is not served by a named route
+    UnnamedRoute(String),
+    /// The value cannot be turned into parameters
+    InvalidParts(String),
+    /// The generated path cannot be used as a header value
+    InvalidHeaderValue(String),
+    /// The registry's lock was poisoned by a panicking thread
+    PoisonedLock,
+    /// An absolute URL was requested but no base URL is configured
+    MissingBaseUrl,
+    /// The value cannot be parsed as a URL
+    InvalidUrl(String),
+    /// A URL must be signed but no signing key is configured
+    MissingSigningKey,
+    /// The URL's signature is missing or does not match
+    InvalidSignature,
+    /// The URL's signature has expired
+    ExpiredSignature,
+}
+
+impl Display for NamedRoutesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRoute(name) => write!(f, "no route named `{}`", name),
+            Self::MissingParameter { route, parameter } => write!(
+                f,
+                "route `{}` requires the parameter `{}`",
+                route, parameter
+            ),
+            Self::ExtraParameter { route, parameter } => write!(
+                f,
+                "route `{}` does not have the parameter `{}`",
+                route, parameter
+            ),
+            Self::EmptyParameter { route, parameter } => write!(
+                f,
+                "route `{}` was given an empty value for the parameter `{}`",
+                route, parameter
+            ),
+            Self::ConflictingParameter { route, parameter } => write!(
+                f,
+                "route `{}` was given the positional value {} for a parameter that is already named",
+                route, parameter
+            ),
+            Self::MethodNotAllowed { route, method } => {
+                write!(f, "route `{}` does not accept {} requests", route, method)
+            }
+            Self::UnnamedRoute(path) => write!(f, "`{}` is not served by a named route", path),
+            Self::InvalidParts(reason) => write!(f, "invalid route parameters: {}", reason),
+            Self::InvalidHeaderValue(value) => {
+                write!(f, "`{}` is not a valid header value", value)
+            }
+            Self::PoisonedLock => write!(f, "the route registry lock is poisoned"),
+            Self::MissingBaseUrl => write!(f, "no base URL is configured"),
+            Self::InvalidUrl(value) => write!(f, "`{}` is not a valid URL", value),
+            Self::MissingSigningKey => write!(f, "no signing key is configured"),
+            Self::InvalidSignature => write!(f, "the URL's signature is invalid"),
+            Self::ExpiredSignature => write!(f, "the URL's signature has expired"),
+        }
+    }
+}
+
+impl Error for NamedRoutesError {}
+
+impl IntoResponse for NamedRoutesError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::InvalidSignature | Self::ExpiredSignature => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
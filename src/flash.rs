@@ -0,0 +1,371 @@
+use std::{
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    signed_url::{unix_timestamp, SigningKeys},
+    NamedRoutesError, RouteRegistry,
+};
+
+/// Name of the cookie that carries the flash messages
+pub const FLASH_COOKIE: &str = "named_routes_flash";
+
+/// How long flash messages are kept when they are not read
+const FLASH_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// One-shot messages set by `Redirector::with_flash` on the previous response.
+///
+/// The messages are read from a signed cookie that expires after five minutes.
+/// On routes added with `RouterWrapper` the cookie is cleared once the extractor
+/// has read it, so the messages are shown once. The cookie is `Secure` unless the
+/// registry's base URL uses http, see `RouteRegistry::set_secure_cookies`.
+///
+/// ```rust
+///  use axum::response::Html;
+///  use named_routes_axum::Flash;
+///
+/// async fn handler(flash: Flash) -> Html<String> {
+///     Html(flash.get("success").unwrap_or_default().to_string())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Flash {
+    messages: Vec<(String, String)>,
+}
+
+/// Set by the `Flash` extractor when the request carried a flash cookie
+#[derive(Debug, Clone, Default)]
+struct FlashRead(Arc<AtomicBool>);
+
+impl Flash {
+    /// Reads the messages from a request's cookies. Messages with an invalid
+    /// signature or that have expired are dropped
+    pub fn from_parts(parts: &Parts) -> Self {
+        let keys = RouteRegistry::from_parts(parts).signing_keys();
+
+        match flash_cookie(&parts.headers) {
+            Some(value) => {
+                if let Some(read) = parts.extensions.get::<FlashRead>() {
+                    read.0.store(true, Ordering::Relaxed);
+                }
+
+                Self {
+                    messages: decode(&keys, &value).unwrap_or_default(),
+                }
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the first message stored under `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, message)| message.as_str())
+    }
+
+    /// Returns every message in the order they were added
+    pub fn messages(&self) -> &[(String, String)] {
+        &self.messages
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Flash {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts))
+    }
+}
+
+/// Clears the flash cookie once the `Flash` extractor has read it, unless the
+/// response sets new messages. Added to every route by `RouterWrapper::route`
+pub(crate) async fn clear_read_flash(mut request: Request, next: Next) -> Response {
+    let read = FlashRead::default();
+    request.extensions_mut().insert(read.clone());
+
+    let secure = request
+        .extensions()
+        .get::<RouteRegistry>()
+        .cloned()
+        .unwrap_or_else(RouteRegistry::global)
+        .settings()
+        .secure_cookies();
+
+    let mut response = next.run(request).await;
+    if read.0.load(Ordering::Relaxed) && !sets_flash(response.headers()) {
+        let cookie = format!("{}=; Path=/; Max-Age=0{}", FLASH_COOKIE, attributes(secure));
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+    }
+
+    response
+}
+
+/// Returns the `Set-Cookie` value that carries the signed messages
+pub(crate) fn set_cookie(
+    keys: &SigningKeys,
+    messages: &[(String, String)],
+    secure: bool,
+) -> Result<HeaderValue, NamedRoutesError> {
+    encode(
+        keys,
+        messages,
+        unix_timestamp(SystemTime::now() + FLASH_MAX_AGE),
+        secure,
+    )
+}
+
+fn encode(
+    keys: &SigningKeys,
+    messages: &[(String, String)],
+    expires: u64,
+    secure: bool,
+) -> Result<HeaderValue, NamedRoutesError> {
+    let payload = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(messages)
+        .finish();
+    let signature = keys.sign(&signed_data(&payload, expires))?;
+    let cookie = format!(
+        "{}={}.{}.{}; Path=/; Max-Age={}{}",
+        FLASH_COOKIE,
+        payload,
+        expires,
+        signature,
+        FLASH_MAX_AGE.as_secs(),
+        attributes(secure)
+    );
+
+    HeaderValue::from_str(&cookie).map_err(|_| NamedRoutesError::InvalidHeaderValue(cookie))
+}
+
+/// The attributes shared by the cookie that sets the messages and the one that clears them
+fn attributes(secure: bool) -> &'static str {
+    if secure {
+        "; HttpOnly; Secure; SameSite=Lax"
+    } else {
+        "; HttpOnly; SameSite=Lax"
+    }
+}
+
+fn decode(keys: &SigningKeys, value: &str) -> Option<Vec<(String, String)>> {
+    let (rest, signature) = value.rsplit_once('.')?;
+    let (payload, expires) = rest.rsplit_once('.')?;
+    let expires = expires.parse::<u64>().ok()?;
+    if !keys.verify(&signed_data(payload, expires), signature)
+        || unix_timestamp(SystemTime::now()) > expires
+    {
+        return None;
+    }
+
+    Some(
+        form_urlencoded::parse(payload.as_bytes())
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect(),
+    )
+}
+
+/// Keeps flash signatures apart from signed URL signatures made with the same keys
+fn signed_data(payload: &str, expires: u64) -> String {
+    format!("flash:{}:{}", expires, payload)
+}
+
+fn flash_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == FLASH_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Returns true when the headers set the flash cookie
+fn sets_flash(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| {
+            value
+                .strip_prefix(FLASH_COOKIE)
+                .is_some_and(|rest| rest.starts_with('='))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        response::Response,
+    };
+    use tower::ServiceExt;
+
+    use super::{encode, Flash};
+    use crate::{
+        signed_url::SigningKeys,
+        test_support::{body_string, registry_with},
+        RouterWrapper,
+    };
+
+    fn cookie_of(response: &Response) -> String {
+        let set_cookie = response.headers().get(header::SET_COOKIE).unwrap();
+        set_cookie
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn show(flash: Flash) -> String {
+        flash.get("success").unwrap_or("none").to_string()
+    }
+
+    fn request(uri: &str, cookie: &str) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .header(header::COOKIE, cookie)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flash_round_trip() {
+        let registry = registry_with(&[("home", "/")]);
+        registry.set_signing_keys(["secret"]);
+
+        let redirect = registry
+            .service()
+            .try_get("home")
+            .unwrap()
+            .redirector()
+            .with_flash("success", "Saved; all good")
+            .try_redirect(())
+            .unwrap();
+        assert_eq!(redirect.status(), StatusCode::FOUND);
+        let set_cookie = redirect.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(set_cookie.contains("; Max-Age=300; HttpOnly; Secure; SameSite=Lax"));
+        let cookie = cookie_of(&redirect);
+
+        let app = RouterWrapper::<()>::with_registry(registry)
+            .get("/", show, "home")
+            .get("/other", || async { "other" }, "other")
+            .into_router();
+
+        let response = app
+            .clone()
+            .oneshot(request("/other", &cookie))
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+
+        let response = app
+            .clone()
+            .oneshot(request("/", &format!("theme=dark; {}", cookie)))
+            .await
+            .unwrap();
+        assert!(cookie_of(&response).ends_with("named_routes_flash="));
+        assert_eq!(body_string(response).await, "Saved; all good");
+
+        let tampered = cookie.replace("Saved", "Lost");
+        let response = app.oneshot(request("/", &tampered)).await.unwrap();
+        assert_eq!(body_string(response).await, "none");
+    }
+
+    #[tokio::test]
+    async fn test_expired_flash_is_dropped() {
+        let registry = registry_with(&[("home", "/")]);
+        registry.set_signing_keys(["secret"]);
+        let messages = vec![("success".to_string(), "Saved".to_string())];
+        let expired = encode(&SigningKeys::new(["secret"]), &messages, 1, true).unwrap();
+        let cookie = expired.to_str().unwrap().split(';').next().unwrap();
+
+        let app = RouterWrapper::<()>::with_registry(registry)
+            .get("/", show, "home")
+            .into_router();
+        let response = app.oneshot(request("/", cookie)).await.unwrap();
+
+        assert!(cookie_of(&response).ends_with("named_routes_flash="));
+        assert_eq!(body_string(response).await, "none");
+    }
+
+    #[tokio::test]
+    async fn test_flash_cookie_over_plain_http() {
+        let registry = registry_with(&[("home", "/")]);
+        registry
+            .set_signing_keys(["secret"])
+            .set_base_url("http://localhost:3000".parse().unwrap());
+
+        let redirect = registry
+            .service()
+            .try_get("home")
+            .unwrap()
+            .redirector()
+            .with_flash("success", "Saved")
+            .try_redirect(())
+            .unwrap();
+        let set_cookie = redirect.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(set_cookie.ends_with("; Max-Age=300; HttpOnly; SameSite=Lax"));
+
+        let app = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/", show, "home")
+            .into_router();
+        let response = app
+            .oneshot(request("/", &cookie_of(&redirect)))
+            .await
+            .unwrap();
+        let cleared = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(!cleared.contains("Secure"));
+        assert_eq!(body_string(response).await, "Saved");
+
+        registry.set_secure_cookies(true);
+        let redirect = registry
+            .service()
+            .try_get("home")
+            .unwrap()
+            .redirector()
+            .with_flash("success", "Saved")
+            .try_redirect(())
+            .unwrap();
+        let set_cookie = redirect.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(set_cookie.contains("; Secure"));
+    }
+
+    #[test]
+    fn test_flash_without_a_signing_key() {
+        let result = registry_with(&[("home", "/")])
+            .service()
+            .try_get("home")
+            .unwrap()
+            .redirector()
+            .with_flash("success", "Saved")
+            .try_redirect(());
+
+        assert_eq!(
+            result.err(),
+            Some(crate::NamedRoutesError::MissingSigningKey)
+        );
+    }
+}
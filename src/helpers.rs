@@ -1,13 +1,21 @@
+//! Shortcuts that resolve names against the process wide registry
+//!
+//! The functions without a prefix panic when the route is unknown and the `try_*`
+//! functions return `None`. The `checked_*` functions return a `NamedRoutesError`
+//! and validate the parts as done by `RoutePath::try_with`.
+//!
+//! Only the `checked_*` redirects refuse a route the client cannot follow with a
+//! GET, see `Redirector::try_redirect_as`.
+
 use axum::{body::Body, http::Response, response::IntoResponse};
 
-use crate::{NamedRoutesService, PartsValue};
+use crate::{NamedRoutesError, NamedRoutesService, PartsValue, QueryParams, RedirectKind};
 
 pub fn redirect_with<V: Into<PartsValue>>(parts: V, route_name: &str) -> impl IntoResponse {
     NamedRoutesService::new()
-        .get(route_name)
-        .unwrap()
-        .with(parts)
-        .redirect("")
+        .try_get(route_name)
+        .map(|route| route.with(parts).redirect(""))
+        .unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn try_redirect_with<V: Into<PartsValue>>(
@@ -19,11 +27,22 @@ pub fn try_redirect_with<V: Into<PartsValue>>(
         .map(|route| route.with(parts).redirect(""))
 }
 
+/// Like `redirect_with` but returns an error instead of panicking
+pub fn checked_redirect_with<V: Into<PartsValue>>(
+    parts: V,
+    route_name: &str,
+) -> Result<Response<Body>, NamedRoutesError> {
+    NamedRoutesService::new()
+        .try_get(route_name)?
+        .try_with(parts)?
+        .try_redirect("")
+}
+
 pub fn redirect(route_name: &str) -> impl IntoResponse {
     NamedRoutesService::new()
-        .get(route_name)
-        .unwrap()
-        .redirect("")
+        .try_get(route_name)
+        .map(|route| route.redirect(""))
+        .unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn try_redirect(route_name: &str) -> Option<Response<Body>> {
@@ -32,6 +51,74 @@ pub fn try_redirect(route_name: &str) -> Option<Response<Body>> {
         .map(|route| route.redirect(""))
 }
 
+/// Like `redirect` but returns an error instead of panicking
+pub fn checked_redirect(route_name: &str) -> Result<Response<Body>, NamedRoutesError> {
+    NamedRoutesService::new()
+        .try_get(route_name)?
+        .try_redirect("")
+}
+
+/// Redirects to the route with the given kind, panics when the route is unknown
+pub fn redirect_as(route_name: &str, kind: RedirectKind) -> impl IntoResponse {
+    NamedRoutesService::new()
+        .try_get(route_name)
+        .map(|route| route.redirect_as(kind, ""))
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Like `redirect_as` but returns an error instead of panicking
+pub fn checked_redirect_as(
+    route_name: &str,
+    kind: RedirectKind,
+) -> Result<Response<Body>, NamedRoutesError> {
+    NamedRoutesService::new()
+        .try_get(route_name)?
+        .try_redirect_as(kind, "")
+}
+
+pub fn redirect_with_as<V: Into<PartsValue>>(
+    parts: V,
+    route_name: &str,
+    kind: RedirectKind,
+) -> impl IntoResponse {
+    NamedRoutesService::new()
+        .try_get(route_name)
+        .map(|route| route.with(parts).redirect_as(kind, ""))
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Like `redirect_with_as` but returns an error instead of panicking
+pub fn checked_redirect_with_as<V: Into<PartsValue>>(
+    parts: V,
+    route_name: &str,
+    kind: RedirectKind,
+) -> Result<Response<Body>, NamedRoutesError> {
+    NamedRoutesService::new()
+        .try_get(route_name)?
+        .try_with(parts)?
+        .try_redirect_as(kind, "")
+}
+
+/// Redirects to the route with see other/303
+pub fn see_other(route_name: &str) -> impl IntoResponse {
+    redirect_as(route_name, RedirectKind::SeeOther)
+}
+
+/// Redirects to the route with temporary redirect/307
+pub fn temporary(route_name: &str) -> impl IntoResponse {
+    redirect_as(route_name, RedirectKind::Temporary)
+}
+
+/// Redirects to the route with moved permanently/301
+pub fn permanent(route_name: &str) -> impl IntoResponse {
+    redirect_as(route_name, RedirectKind::Permanent)
+}
+
+/// Redirects to the route with permanent redirect/308
+pub fn permanent_redirect(route_name: &str) -> impl IntoResponse {
+    redirect_as(route_name, RedirectKind::PermanentRedirect)
+}
+
 pub fn has_route(route_name: &str) -> bool {
     NamedRoutesService::new().has(route_name)
 }
@@ -42,19 +129,225 @@ pub fn has_parts(route_name: &str) -> bool {
 }
 
 pub fn get_path(route_name: &str) -> String {
-    NamedRoutesService::new().get_path(route_name).unwrap()
+    checked_get_path(route_name).unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn try_get_path(route_name: &str) -> Option<String> {
     NamedRoutesService::new().get_path(route_name)
 }
 
+/// Like `get_path` but returns an error instead of panicking
+pub fn checked_get_path(route_name: &str) -> Result<String, NamedRoutesError> {
+    NamedRoutesService::new().try_get_path(route_name)
+}
+
 pub fn get_path_with<V: Into<PartsValue>>(route_name: &str, parts: V) -> String {
     NamedRoutesService::new()
-        .get_path_with(route_name, parts)
-        .unwrap()
+        .try_get(route_name)
+        .map(|route| route.with(parts).path())
+        .unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn try_get_path_with<V: Into<PartsValue>>(route_name: &str, parts: V) -> Option<String> {
     NamedRoutesService::new().get_path_with(route_name, parts)
 }
+
+/// Like `get_path_with` but returns an error instead of panicking
+pub fn checked_get_path_with<V: Into<PartsValue>>(
+    route_name: &str,
+    parts: V,
+) -> Result<String, NamedRoutesError> {
+    NamedRoutesService::new().try_get_path_with(route_name, parts)
+}
+
+pub fn get_path_with_query<V: Into<PartsValue>, Q: Into<QueryParams>>(
+    route_name: &str,
+    parts: V,
+    query: Q,
+) -> String {
+    NamedRoutesService::new()
+        .try_get(route_name)
+        .map(|route| route.with(parts).with_query(query).path())
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+pub fn try_get_path_with_query<V: Into<PartsValue>, Q: Into<QueryParams>>(
+    route_name: &str,
+    parts: V,
+    query: Q,
+) -> Option<String> {
+    NamedRoutesService::new().get_path_with_query(route_name, parts, query)
+}
+
+/// Like `get_path_with_query` but returns an error instead of panicking
+pub fn checked_get_path_with_query<V: Into<PartsValue>, Q: Into<QueryParams>>(
+    route_name: &str,
+    parts: V,
+    query: Q,
+) -> Result<String, NamedRoutesError> {
+    NamedRoutesService::new().try_get_path_with_query(route_name, parts, query)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::http::{header::LOCATION, Method, StatusCode};
+    use tracing::{span, Event, Level, Metadata, Subscriber};
+
+    use super::*;
+    use crate::RouteRegistry;
+
+    fn setup() {
+        RouteRegistry::global()
+            .register("helpers.home", "/helpers")
+            .register("helpers.user", "/helpers/{id}")
+            .register_methods("helpers.store", "/helpers/store", &[Method::POST]);
+    }
+
+    #[test]
+    fn test_paths() {
+        setup();
+
+        assert_eq!(get_path("helpers.home"), "/helpers");
+        assert_eq!(get_path_with("helpers.user", (7,)), "/helpers/7");
+        assert_eq!(
+            get_path_with_query("helpers.user", (7,), [("tab", "posts")]),
+            "/helpers/7?tab=posts"
+        );
+        assert!(has_route("helpers.home"));
+        assert!(has_parts("helpers.user"));
+        assert!(!has_parts("helpers.unknown"));
+    }
+
+    #[test]
+    fn test_try_paths_return_none() {
+        setup();
+
+        assert_eq!(try_get_path("helpers.home"), Some("/helpers".to_string()));
+        assert_eq!(try_get_path("helpers.unknown"), None);
+        assert_eq!(
+            try_get_path_with("helpers.user", ()),
+            Some("/helpers/".to_string())
+        );
+        assert_eq!(
+            try_get_path_with_query("helpers.user", (7,), [("a", "b")]),
+            Some("/helpers/7?a=b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_checked_paths_return_errors() {
+        setup();
+
+        assert_eq!(checked_get_path("helpers.home"), Ok("/helpers".to_string()));
+        assert_eq!(
+            checked_get_path("helpers.unknown"),
+            Err(NamedRoutesError::UnknownRoute(
+                "helpers.unknown".to_string()
+            ))
+        );
+        assert!(matches!(
+            checked_get_path_with("helpers.user", ()),
+            Err(NamedRoutesError::MissingParameter { .. })
+        ));
+        assert_eq!(
+            checked_get_path_with_query("helpers.user", (7,), [("a", "b")]),
+            Ok("/helpers/7?a=b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_redirects() {
+        setup();
+
+        let response = try_redirect_with((3,), "helpers.user").unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.headers()[LOCATION], "/helpers/3");
+        assert_eq!(
+            checked_redirect_with((3,), "helpers.user")
+                .unwrap()
+                .headers()[LOCATION],
+            "/helpers/3"
+        );
+
+        let response = checked_redirect_as("helpers.home", RedirectKind::SeeOther).unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            checked_redirect_with_as((3,), "helpers.user", RedirectKind::Permanent)
+                .unwrap()
+                .status(),
+            StatusCode::MOVED_PERMANENTLY
+        );
+        assert_eq!(
+            temporary("helpers.home").into_response().status(),
+            StatusCode::TEMPORARY_REDIRECT
+        );
+        assert!(try_redirect("helpers.unknown").is_none());
+        assert!(checked_redirect("helpers.unknown").is_err());
+    }
+
+    /// Counts the warnings logged with `tracing`
+    struct Warnings(Arc<AtomicUsize>);
+
+    impl Subscriber for Warnings {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if *event.metadata().level() == Level::WARN {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_only_checked_redirects_check_the_method() {
+        setup();
+        let warnings = Arc::new(AtomicUsize::new(0));
+
+        tracing::subscriber::with_default(Warnings(warnings.clone()), || {
+            let response = redirect("helpers.store").into_response();
+            assert_eq!(response.status(), StatusCode::FOUND);
+            assert_eq!(response.headers()[LOCATION], "/helpers/store");
+            assert_eq!(
+                see_other("helpers.store").into_response().status(),
+                StatusCode::SEE_OTHER
+            );
+            assert!(try_redirect("helpers.store").is_some());
+            assert_eq!(warnings.load(Ordering::SeqCst), 3);
+
+            let _ = temporary("helpers.store");
+            let _ = redirect("helpers.home");
+            assert_eq!(warnings.load(Ordering::SeqCst), 3);
+        });
+
+        assert!(matches!(
+            checked_redirect("helpers.store"),
+            Err(NamedRoutesError::MethodNotAllowed { .. })
+        ));
+        assert!(checked_redirect_as("helpers.store", RedirectKind::Temporary).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "helpers.missing")]
+    fn test_redirect_panics_for_unknown_routes() {
+        let _ = redirect("helpers.missing");
+    }
+}
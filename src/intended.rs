@@ -0,0 +1,175 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, OriginalUri},
+    http::{request::Parts, Response},
+};
+
+use crate::{NamedRoutesError, NamedRoutesService, Redirector, RouteRegistry};
+
+/// Name of the query parameter that carries the intended URL
+pub const INTENDED_PARAM: &str = "next";
+
+/// Remembers the URL a visitor asked for while they log in.
+///
+/// `redirect_to_login` sends the visitor to the login route with the current URL in the
+/// `next` query parameter. After logging in, `redirect` sends them to that URL when it
+/// belongs to one of the registered routes and to the default route otherwise.
+///
+/// ```rust
+///  use axum::response::Response;
+///  use named_routes_axum::{Intended, NamedRoutesError};
+///
+/// async fn dashboard(intended: Intended) -> Result<Response, NamedRoutesError> {
+///     intended.redirect_to_login("login")
+/// }
+///
+/// async fn login(intended: Intended) -> Result<Response, NamedRoutesError> {
+///     intended.redirect("home")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Intended {
+    current: String,
+    next: Option<String>,
+    service: NamedRoutesService,
+}
+
+impl Intended {
+    /// Builds the value from a request's parts
+    pub fn from_parts(parts: &Parts) -> Self {
+        let registry = RouteRegistry::from_parts(parts);
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|uri| &uri.0)
+            .unwrap_or(&parts.uri);
+
+        let current = uri
+            .path_and_query()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| "/".to_string());
+        let next = uri.query().and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == INTENDED_PARAM)
+                .map(|(_, value)| value.into_owned())
+        });
+
+        Self {
+            current,
+            next,
+            service: registry.service(),
+        }
+    }
+
+    /// Returns the intended URL given in the request's query
+    pub fn next(&self) -> Option<&str> {
+        self.next.as_deref()
+    }
+
+    /// Returns the redirector to the login route, with the current URL as `next`
+    pub fn login_redirector(&self, login_route: &str) -> Result<Redirector, NamedRoutesError> {
+        Ok(self
+            .service
+            .try_get(login_route)?
+            .redirector()
+            .with_query((INTENDED_PARAM, self.current.as_str())))
+    }
+
+    pub fn redirect_to_login(&self, login_route: &str) -> Result<Response<Body>, NamedRoutesError> {
+        self.login_redirector(login_route)?.try_redirect(())
+    }
+
+    /// Returns the redirector to the intended URL or to the default route
+    pub fn redirector(&self, default_route: &str) -> Result<Redirector, NamedRoutesError> {
+        self.service
+            .intended_redirector(self.next.as_deref(), default_route)
+    }
+
+    pub fn redirect(&self, default_route: &str) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirector(default_route)?.try_redirect(())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Intended {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Intended;
+    use crate::{
+        test_support::{location, registry_with, request_parts},
+        RouteRegistry,
+    };
+
+    const ROUTES: &[(&str, &str)] = &[
+        ("home", "/"),
+        ("login", "/login"),
+        ("invoice", "/invoices/{id}"),
+    ];
+
+    fn intended(registry: &RouteRegistry, uri: &str) -> Intended {
+        Intended::from_parts(&request_parts(registry, uri, &[]))
+    }
+
+    #[test]
+    fn test_redirect_to_login_keeps_the_current_url() {
+        let registry = registry_with(ROUTES);
+        let response = intended(&registry, "/invoices/7?tab=items")
+            .redirect_to_login("login")
+            .unwrap();
+
+        assert_eq!(
+            location(&response),
+            "/login?next=%2Finvoices%2F7%3Ftab%3Ditems"
+        );
+    }
+
+    #[test]
+    fn test_redirect_to_the_intended_url() {
+        let registry = registry_with(ROUTES);
+
+        let login = intended(&registry, "/login?next=%2Finvoices%2F7%3Ftab%3Ditems");
+        assert_eq!(login.next(), Some("/invoices/7?tab=items"));
+        assert_eq!(
+            location(&login.redirect("home").unwrap()),
+            "/invoices/7?tab=items"
+        );
+
+        for next in ["https%3A%2F%2Fevil.com%2F", "%2F%2Fevil.com", "%2Funknown"] {
+            let login = intended(&registry, &format!("/login?next={}", next));
+            assert_eq!(location(&login.redirect("home").unwrap()), "/");
+        }
+    }
+
+    #[test]
+    fn test_protocol_relative_intended_urls_are_refused() {
+        let registry = registry_with(&[("home", "/"), ("spa", "/{*path}")]);
+        registry.set_base_url("https://example.com".parse().unwrap());
+
+        for next in [
+            "https%3A%2F%2Fexample.com%2F%2Fevil.com",
+            "%2F%2Fevil.com%2Fdocs",
+        ] {
+            let login = intended(&registry, &format!("/login?next={}", next));
+            assert_eq!(location(&login.redirect("home").unwrap()), "/", "{}", next);
+        }
+
+        let login = intended(&registry, "/login?next=https%3A%2F%2Fexample.com%2Fdocs");
+        assert_eq!(location(&login.redirect("home").unwrap()), "/docs");
+
+        for (next, expected) in [
+            ("%2F%252Fevil.com", "/%2Fevil.com"),
+            ("%2F%252F%252Fevil.com", "/%2F%2Fevil.com"),
+        ] {
+            let login = intended(&registry, &format!("/login?next={}", next));
+            assert_eq!(location(&login.redirect("home").unwrap()), expected);
+        }
+    }
+}
@@ -17,15 +17,35 @@
 //!
 //! ```
 //!
+mod base_url;
+mod client_redirect;
+mod current_route;
+mod encoding;
+mod error;
+mod flash;
+mod intended;
 mod name_repo;
+mod query;
+mod redirect_back;
 mod redirector;
+mod registry;
+mod route_match;
+mod route_meta;
 mod route_path;
 mod router_wrapper;
+mod safe_target;
+#[cfg(feature = "serde")]
+mod serde_parts;
 mod service;
+mod signed_url;
+mod template;
+#[cfg(test)]
+mod test_support;
+mod url_generator;
 
 pub mod helpers;
 
-pub(crate) static NAME_ROUTES_REPO: OnceLock<RepoInner> = OnceLock::new();
+pub(crate) static NAME_ROUTES_REPO: OnceLock<RouteRegistry> = OnceLock::new();
 pub(crate) type RepoInner = Arc<RwLock<HashMap<String, RoutePath>>>;
 
 use std::{
@@ -35,6 +55,23 @@ use std::{
 
 pub(crate) use name_repo::*;
 
+pub use base_url::BaseUrl;
+pub use client_redirect::{ClientRedirect, RedirectClient};
+pub use current_route::CurrentRoute;
+pub use error::NamedRoutesError;
+pub use flash::{Flash, FLASH_COOKIE};
+pub use intended::{Intended, INTENDED_PARAM};
+pub use query::QueryParams;
+pub use redirect_back::RedirectBack;
+pub use redirector::{RedirectKind, Redirector};
+pub use registry::{DuplicatePolicy, DuplicateRoute, RouteRegistry};
+pub use route_match::RouteMatch;
+pub use route_meta::RouteMeta;
 pub use route_path::*;
 pub use router_wrapper::*;
+pub use safe_target::SafeTarget;
+#[cfg(feature = "serde")]
+pub use serde_parts::Serialized;
 pub use service::NamedRoutesService;
+pub use signed_url::ValidSignature;
+pub use url_generator::{TrustedProxies, UrlGenerator};
@@ -1,52 +1,68 @@
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use axum::http::Method;
 
-use crate::{RepoInner, NAME_ROUTES_REPO};
+use crate::RouteRegistry;
 
 #[derive(Debug, Clone)]
 pub struct NamedRoutesRepo {
     prefix: Option<String>,
-    repo: RepoInner,
+    name_prefix: String,
+    repo: RouteRegistry,
 }
 
 impl Default for NamedRoutesRepo {
     fn default() -> Self {
         Self {
             prefix: None,
-            repo: Arc::clone(
-                NAME_ROUTES_REPO.get_or_init(|| Arc::new(RwLock::new(HashMap::new()))),
-            ),
+            name_prefix: String::new(),
+            repo: RouteRegistry::global(),
         }
     }
 }
 
 impl NamedRoutesRepo {
     pub fn new(prefix: Option<&str>) -> Self {
+        Self::with_registry(&RouteRegistry::global(), prefix)
+    }
+
+    pub fn with_registry(registry: &RouteRegistry, prefix: Option<&str>) -> Self {
         Self {
             prefix: prefix.map(|p| p.to_string()),
-
-            repo: Arc::clone(
-                NAME_ROUTES_REPO.get_or_init(|| Arc::new(RwLock::new(HashMap::new()))),
-            ),
+            name_prefix: String::new(),
+            repo: registry.clone(),
         }
     }
+
+    /// Sets the text prepended to every registered name, e.g. `admin.`
+    pub fn with_name_prefix(mut self, name_prefix: &str) -> Self {
+        self.name_prefix = name_prefix.to_string();
+        self
+    }
+
+    /// Returns the name with the name prefix
+    pub fn qualified_name(&self, name: &str) -> String {
+        format!("{}{}", self.name_prefix, name)
+    }
+
     pub fn register(&self, name: &str, url: &str) -> &Self {
-        if let Ok(mut write_lock) = self.repo.write() {
-            if self.prefix.is_some() {
-                write_lock.insert(
-                    name.to_string(),
-                    format!("{}{}", self.prefix.as_ref().unwrap(), url).into(),
-                );
-            } else {
-                write_lock.insert(name.to_string(), url.into());
-            }
+        self.register_methods(name, url, &[])
+    }
+
+    pub fn register_methods(&self, name: &str, url: &str, methods: &[Method]) -> &Self {
+        let name = &self.qualified_name(name);
+        if let Some(prefix) = &self.prefix {
+            self.repo
+                .register_methods(name, &format!("{}{}", prefix, url), methods);
+        } else {
+            self.repo.register_methods(name, url, methods);
         }
 
         self
     }
 
+    pub fn registry(&self) -> &RouteRegistry {
+        &self.repo
+    }
+
     pub(crate) fn build_child_prefix(&self, child_prefix: &str) -> String {
         let parent = if let Some(p) = self.prefix.as_ref() {
             p.as_str()
@@ -56,4 +72,8 @@ impl NamedRoutesRepo {
 
         format!("{}{}", parent, child_prefix)
     }
+
+    pub(crate) fn build_child_name_prefix(&self, child_prefix: &str) -> String {
+        self.qualified_name(child_prefix)
+    }
 }
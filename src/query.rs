@@ -0,0 +1,165 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Key/value pairs that are appended to a path as its query string.
+///
+/// Keys are sorted when the query string is built so the same values always
+/// produce the same URL. Repeated keys keep the order they were added in.
+///
+/// ```rust
+///  use named_routes_axum::QueryParams;
+///
+/// let query = QueryParams::new()
+///     .append("sort", "name")
+///     .append("tag", "a b")
+///     .append("page", 2)
+///     .append("tag", "c");
+///
+/// assert_eq!(query.to_query_string(), "page=2&sort=name&tag=a+b&tag=c");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QueryParams(Vec<(String, String)>);
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pair. Existing pairs with the same key are kept
+    pub fn append<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.0.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds all the pairs in `other`
+    pub fn extend(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the form encoded query string without the leading `?`
+    pub fn to_query_string(&self) -> String {
+        let mut out = String::with_capacity(self.len_hint());
+        // Writing to a `String` does not fail
+        let _ = self.write_query_string(&mut out);
+        out
+    }
+
+    /// Writes the form encoded query string, without the leading `?`, to `out`
+    pub(crate) fn write_query_string<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        let mut pairs = self.0.iter().collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (pos, (key, value)) in pairs.into_iter().enumerate() {
+            if pos > 0 {
+                out.write_char('&')?;
+            }
+            for chunk in form_urlencoded::byte_serialize(key.as_bytes()) {
+                out.write_str(chunk)?;
+            }
+            out.write_char('=')?;
+            for chunk in form_urlencoded::byte_serialize(value.as_bytes()) {
+                out.write_str(chunk)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The length of the query string and its leading `?` when nothing needs to be encoded
+    pub(crate) fn len_hint(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(key, value)| key.len() + value.len() + 2)
+            .sum()
+    }
+}
+
+impl<K: ToString, V: ToString> From<(K, V)> for QueryParams {
+    fn from(value: (K, V)) -> Self {
+        Self::new().append(value.0, value.1)
+    }
+}
+
+impl<K: ToString, V: ToString> From<Vec<(K, V)>> for QueryParams {
+    fn from(value: Vec<(K, V)>) -> Self {
+        value
+            .into_iter()
+            .fold(Self::new(), |acc, (k, v)| acc.append(k, v))
+    }
+}
+
+impl<K: ToString, V: ToString, const N: usize> From<[(K, V); N]> for QueryParams {
+    fn from(value: [(K, V); N]) -> Self {
+        value
+            .into_iter()
+            .fold(Self::new(), |acc, (k, v)| acc.append(k, v))
+    }
+}
+
+impl<K: ToString, V: ToString> From<&[(K, V)]> for QueryParams {
+    fn from(value: &[(K, V)]) -> Self {
+        value.iter().fold(Self::new(), |acc, (k, v)| {
+            acc.append(k.to_string(), v.to_string())
+        })
+    }
+}
+
+impl<K: ToString, V: ToString> From<HashMap<K, V>> for QueryParams {
+    fn from(value: HashMap<K, V>) -> Self {
+        value
+            .into_iter()
+            .fold(Self::new(), |acc, (k, v)| acc.append(k, v))
+    }
+}
+
+impl<K: ToString, V: ToString> From<BTreeMap<K, V>> for QueryParams {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        value
+            .into_iter()
+            .fold(Self::new(), |acc, (k, v)| acc.append(k, v))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::QueryParams;
+
+    #[test]
+    fn test_keys_are_sorted() {
+        let mut map = HashMap::new();
+        map.insert("sort", "name");
+        map.insert("page", "2");
+        map.insert("filter", "active");
+
+        assert_eq!(
+            QueryParams::from(map).to_query_string(),
+            "filter=active&page=2&sort=name"
+        );
+    }
+
+    #[test]
+    fn test_repeated_keys() {
+        let query = QueryParams::from([("tag", "b"), ("page", "1"), ("tag", "a")]);
+
+        assert_eq!(query.to_query_string(), "page=1&tag=b&tag=a");
+    }
+
+    #[test]
+    fn test_values_are_encoded() {
+        let query = QueryParams::from(vec![("q", "a&b=c d"), ("next", "/users?id=1")]);
+
+        assert_eq!(
+            query.to_query_string(),
+            "next=%2Fusers%3Fid%3D1&q=a%26b%3Dc+d"
+        );
+    }
+}
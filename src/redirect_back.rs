@@ -0,0 +1,197 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{header, request::Parts, Response, Uri},
+};
+
+use crate::{
+    safe_target::relative_path, BaseUrl, NamedRoutesError, NamedRoutesService, Redirector,
+    RouteRegistry, UrlGenerator,
+};
+
+/// Redirects to the page the request came from, as given by the `Referer` header.
+///
+/// The referer is only used when it is on this application's host, as worked out
+/// by `UrlGenerator`, and matches one of the registered routes. Otherwise the
+/// fallback route is used, so the referer cannot be used as an open redirect.
+///
+/// ```rust
+///  use axum::response::Response;
+///  use named_routes_axum::{NamedRoutesError, RedirectBack};
+///
+/// async fn save(back: RedirectBack) -> Result<Response, NamedRoutesError> {
+///     back.redirect("home")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedirectBack {
+    referer: Option<Uri>,
+    base_url: Option<BaseUrl>,
+    service: NamedRoutesService,
+}
+
+impl RedirectBack {
+    /// Builds the redirect from a request's parts
+    pub fn from_parts(parts: &Parts) -> Self {
+        let registry = RouteRegistry::from_parts(parts);
+        let referer = parts
+            .headers
+            .get(header::REFERER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Uri>().ok());
+        let base_url = UrlGenerator::from_parts(parts)
+            .ok()
+            .map(|urls| urls.base_url().clone());
+
+        Self {
+            referer,
+            base_url,
+            service: registry.service(),
+        }
+    }
+
+    /// Returns the redirector to the referer when it belongs to one of the
+    /// registered routes
+    pub fn referer(&self) -> Option<Redirector> {
+        let referer = self.referer.as_ref()?;
+        let path = if referer.authority().is_some() {
+            let base_url = self.base_url.as_ref()?;
+            if !base_url.same_origin(referer) {
+                return None;
+            }
+            relative_path(base_url.strip_prefix(referer.path())?)?
+        } else {
+            let path = relative_path(referer.path())?;
+            self.base_url
+                .as_ref()
+                .and_then(|base_url| base_url.strip_prefix(path))
+                .unwrap_or(path)
+        };
+
+        let path_and_query = match referer.query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path.to_string(),
+        };
+
+        // A catch-all route can turn `//evil.com` back into a protocol relative
+        // location, so the generated path is checked as well
+        let redirector = self.service.redirector_for(&path_and_query)?;
+        relative_path(&redirector.path())?;
+
+        Some(redirector)
+    }
+
+    /// Returns the redirector to the referer or to the fallback route
+    pub fn redirector(&self, fallback: &str) -> Result<Redirector, NamedRoutesError> {
+        match self.referer() {
+            Some(redirector) => Ok(redirector),
+            None => Ok(self.service.try_get(fallback)?.redirector()),
+        }
+    }
+
+    /// Redirects to the referer or to the fallback route
+    pub fn redirect(&self, fallback: &str) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirector(fallback)?.try_redirect(())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RedirectBack {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::header;
+
+    use super::RedirectBack;
+    use crate::{
+        test_support::{registry_with, request_parts},
+        BaseUrl, RouteRegistry,
+    };
+
+    const ROUTES: &[(&str, &str)] = &[
+        ("home", "/"),
+        ("user", "/users/{id}"),
+        ("user.edit", "/users/{id}/edit"),
+    ];
+
+    fn back(registry: &RouteRegistry, referer: Option<&str>) -> RedirectBack {
+        let mut headers = vec![("host", "example.com")];
+        headers.extend(referer.map(|referer| (header::REFERER.as_str(), referer)));
+
+        RedirectBack::from_parts(&request_parts(registry, "/save", &headers))
+    }
+
+    fn location(back: &RedirectBack) -> String {
+        crate::test_support::location(&back.redirect("home").unwrap())
+    }
+
+    #[test]
+    fn test_back_to_a_registered_route() {
+        let registry = registry_with(ROUTES);
+
+        assert_eq!(
+            location(&back(
+                &registry,
+                Some("http://example.com/users/a%20b/edit?tab=2")
+            )),
+            "/users/a%20b/edit?tab=2"
+        );
+        assert_eq!(location(&back(&registry, Some("/users/1"))), "/users/1");
+    }
+
+    #[test]
+    fn test_fallback_for_unknown_or_external_referers() {
+        let registry = registry_with(ROUTES);
+
+        assert_eq!(location(&back(&registry, None)), "/");
+        assert_eq!(
+            location(&back(&registry, Some("http://example.com/unknown"))),
+            "/"
+        );
+        assert_eq!(
+            location(&back(&registry, Some("https://evil.com/users/1"))),
+            "/"
+        );
+        assert_eq!(location(&back(&registry, Some("//evil.com/users/1"))), "/");
+    }
+
+    #[test]
+    fn test_protocol_relative_referer_paths_are_refused() {
+        let registry = registry_with(&[("home", "/"), ("spa", "/{*path}")]);
+
+        assert_eq!(
+            location(&back(&registry, Some("https://example.com//evil.com"))),
+            "/"
+        );
+        assert_eq!(
+            location(&back(&registry, Some("http://example.com//evil.com"))),
+            "/"
+        );
+        assert_eq!(
+            location(&back(&registry, Some("http://example.com/docs/intro"))),
+            "/docs/intro"
+        );
+    }
+
+    #[test]
+    fn test_referer_under_the_base_prefix() {
+        let registry = registry_with(ROUTES);
+        registry.set_base_url("http://example.com/app".parse::<BaseUrl>().unwrap());
+
+        assert_eq!(
+            location(&back(&registry, Some("http://example.com/app/users/1"))),
+            "/users/1"
+        );
+        assert_eq!(
+            location(&back(&registry, Some("http://example.com/users/1"))),
+            "/"
+        );
+    }
+}
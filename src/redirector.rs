@@ -1,41 +1,234 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 use axum::{
     body::Body,
-    http::{header, response::Builder, Response, StatusCode},
+    http::{header, HeaderMap, Method, Response, StatusCode, Uri},
     response::IntoResponse,
 };
 
+use crate::{
+    encoding::{write_path_segment, write_path_segments},
+    flash,
+    registry::RouteSettings,
+    route_path::accepts_method,
+    template::{Segment, Template},
+    BaseUrl, NamedRoutesError, QueryParams,
+};
+
+/// The status code sent with a redirect
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectKind {
+    /// 302 Found
+    #[default]
+    Found,
+    /// 303 See Other. The client follows with a GET, use it after a form post
+    SeeOther,
+    /// 307 Temporary Redirect. The method and the body are kept
+    Temporary,
+    /// 301 Moved Permanently
+    Permanent,
+    /// 308 Permanent Redirect. The method and the body are kept
+    PermanentRedirect,
+}
+
+impl RedirectKind {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Found => StatusCode::FOUND,
+            Self::SeeOther => StatusCode::SEE_OTHER,
+            Self::Temporary => StatusCode::TEMPORARY_REDIRECT,
+            Self::Permanent => StatusCode::MOVED_PERMANENTLY,
+            Self::PermanentRedirect => StatusCode::PERMANENT_REDIRECT,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Redirector {
     parts: Option<HashMap<String, String>>,
-    raw: String,
+    template: Template,
+    pre_encoded: bool,
+    /// Parameters whose values are already percent-encoded
+    encoded: HashSet<String>,
+    query: QueryParams,
+    settings: RouteSettings,
+    absolute: bool,
+    flash: Vec<(String, String)>,
+    methods: Vec<Method>,
 }
 
 impl Redirector {
-    pub(crate) fn new(raw: &str, parts: Option<HashMap<String, String>>) -> Self {
+    /// `parts` maps parameter names, with or without `{}` and `*`, to their values
+    pub(crate) fn new<T: Into<Template>>(
+        template: T,
+        parts: Option<HashMap<String, String>>,
+    ) -> Self {
+        let parts = parts.map(|parts| {
+            parts
+                .into_iter()
+                .map(|(k, v)| {
+                    let name = k
+                        .trim_start_matches('{')
+                        .trim_end_matches('}')
+                        .trim_start_matches('*');
+                    (name.to_string(), v)
+                })
+                .collect()
+        });
+
         Self {
-            raw: raw.to_string(),
+            template: template.into(),
             parts,
+            pre_encoded: false,
+            encoded: HashSet::new(),
+            query: QueryParams::new(),
+            settings: RouteSettings::default(),
+            absolute: false,
+            flash: Vec::new(),
+            methods: Vec::new(),
         }
     }
 
-    /// Returns a reponse instance that will found/302 redirects client
+    pub(crate) fn with_encoded(mut self, encoded: HashSet<String>) -> Self {
+        self.encoded = encoded;
+        self
+    }
+
+    /// Replaces the path with one that is written as it is, e.g. a target
+    /// already checked by `NamedRoutesService::resolve_safe_target`
+    pub(crate) fn with_literal_path(mut self, path: &str) -> Self {
+        self.template = Template::parse(&path.replace('{', "{{").replace('}', "}}"));
+        self.parts = None;
+        self.query = QueryParams::new();
+        self
+    }
+
+    pub(crate) fn with_settings(mut self, settings: RouteSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Appends the given pairs to the path's query string
+    ///
+    /// ```rust
+    ///  use named_routes_axum::RoutePath;
+    ///
+    /// let route = RoutePath::from("/users");
+    ///
+    /// assert_eq!(route.redirector().with_query([("sort", "name"), ("page", "2")]).path(), "/users?page=2&sort=name");
+    /// ```
+    pub fn with_query<Q: Into<QueryParams>>(mut self, query: Q) -> Self {
+        self.query = self.query.extend(query.into());
+        self
+    }
+
+    /// The parameter values are already percent-encoded and will be used as they are.
+    ///
+    /// By default values are percent-encoded so that each one stays a single path segment
+    pub fn pre_encoded(mut self) -> Self {
+        self.pre_encoded = true;
+        self
+    }
+
+    /// Returns a reponse instance that redirects the client with the registry's
+    /// redirect kind, found/302 by default.
+    /// If the path is not a valid `Location` header value, the error's response is returned.
+    ///
+    /// A route the client cannot follow with a GET is only logged with `tracing`
+    /// so that existing redirects keep working, `try_redirect` refuses it
     pub fn redirect<T: IntoResponse>(&self, response: T) -> Response<Body> {
+        self.redirect_as(self.settings.redirect_kind, response)
+    }
+
+    /// Returns a reponse instance that redirects the client with the registry's
+    /// redirect kind, found/302 by default.
+    /// Returns `MethodNotAllowed` as `try_redirect_as` does
+    pub fn try_redirect<T: IntoResponse>(
+        &self,
+        response: T,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.try_redirect_as(self.settings.redirect_kind, response)
+    }
+
+    /// Returns a reponse instance that redirects the client with the given kind.
+    /// If the path is not a valid `Location` header value, the error's response is returned.
+    ///
+    /// Like `redirect` a route the client cannot follow is only logged, see `try_redirect_as`
+    pub fn redirect_as<T: IntoResponse>(&self, kind: RedirectKind, response: T) -> Response<Body> {
+        self.warn_follow_method(kind);
+        self.build_redirect(kind, response)
+            .unwrap_or_else(|error| error.into_response())
+    }
+
+    /// Returns an error when the client follows the redirect with a GET and
+    /// the route does not accept it, e.g. a 302 to a POST only route
+    pub fn try_redirect_as<T: IntoResponse>(
+        &self,
+        kind: RedirectKind,
+        response: T,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.check_follow_method(kind)?;
+        self.build_redirect(kind, response)
+    }
+
+    fn build_redirect<T: IntoResponse>(
+        &self,
+        kind: RedirectKind,
+        response: T,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        let location = self.location()?;
         let mut response = response.into_response();
 
-        response.headers_mut().append(
-            header::LOCATION,
-            header::HeaderValue::from_str(self.path().as_str()).unwrap(),
-        );
-        *response.status_mut() = StatusCode::FOUND;
-        response
+        response.headers_mut().append(header::LOCATION, location);
+        self.append_flash(response.headers_mut())?;
+        *response.status_mut() = kind.status();
+        Ok(response)
+    }
+
+    /// Redirects with see other/303, the client follows with a GET
+    pub fn see_other(&self) -> Response<Body> {
+        self.redirect_as(RedirectKind::SeeOther, ())
+    }
+
+    /// Redirects with temporary redirect/307, the method and the body are kept
+    pub fn temporary(&self) -> Response<Body> {
+        self.redirect_as(RedirectKind::Temporary, ())
+    }
+
+    /// Redirects with moved permanently/301
+    pub fn permanent(&self) -> Response<Body> {
+        self.redirect_as(RedirectKind::Permanent, ())
+    }
+
+    /// Redirects with permanent redirect/308, the method and the body are kept
+    pub fn permanent_redirect(&self) -> Response<Body> {
+        self.redirect_as(RedirectKind::PermanentRedirect, ())
+    }
+
+    /// Adds a one-shot message to the redirect. The messages are stored in a cookie
+    /// signed with the registry's signing keys and are read with the `Flash` extractor
+    pub fn with_flash(mut self, key: &str, message: &str) -> Self {
+        self.flash.push((key.to_string(), message.to_string()));
+        self
+    }
+
+    /// Sets the kind used by `redirect` and `redirect_t`
+    pub fn with_kind(mut self, kind: RedirectKind) -> Self {
+        self.settings.redirect_kind = kind;
+        self
     }
 
     pub fn empty_redirect(&self) -> Response<Body> {
         self.redirect(())
     }
 
+    pub fn try_empty_redirect(&self) -> Result<Response<Body>, NamedRoutesError> {
+        self.try_redirect(())
+    }
+
     /// Returns an html meta tag with a refresh
     ///
     /// ```html
@@ -48,38 +241,172 @@ impl Redirector {
         )
     }
 
+    /// Panics when the path is not a valid `Location` header value.
+    /// A route the client cannot follow is only logged, see `try_redirect_t`
     pub fn redirect_t<T>(&self, body: T) -> Response<T> {
-        Builder::new()
-            .header(
-                header::LOCATION,
-                header::HeaderValue::from_str(self.path().as_str()).unwrap(),
-            )
-            .status(StatusCode::FOUND)
-            .body(body)
-            .unwrap()
+        self.warn_follow_method(self.settings.redirect_kind);
+        self.build_redirect_t(body)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns `MethodNotAllowed` as `try_redirect_as` does
+    pub fn try_redirect_t<T>(&self, body: T) -> Result<Response<T>, NamedRoutesError> {
+        self.check_follow_method(self.settings.redirect_kind)?;
+        self.build_redirect_t(body)
+    }
+
+    fn build_redirect_t<T>(&self, body: T) -> Result<Response<T>, NamedRoutesError> {
+        let location = self.location()?;
+        let mut response = Response::new(body);
+
+        response.headers_mut().insert(header::LOCATION, location);
+        self.append_flash(response.headers_mut())?;
+        *response.status_mut() = self.settings.redirect_kind.status();
+        Ok(response)
+    }
+
+    /// Uses the given base URL for `url` and makes redirects send an absolute `Location`
+    pub fn with_base_url(mut self, base_url: BaseUrl) -> Self {
+        self.settings.base_url = Some(base_url);
+        self.absolute = true;
+        self
+    }
+
+    pub(crate) fn with_methods(mut self, methods: Vec<Method>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Logs what `check_follow_method` would refuse
+    fn warn_follow_method(&self, kind: RedirectKind) {
+        if let Err(error) = self.check_follow_method(kind) {
+            tracing::warn!("redirecting anyway: {}", error);
+        }
     }
 
-    /// Returns the build route's path
+    /// 301, 302 and 303 redirects are followed with a GET
+    pub(crate) fn check_follow_method(&self, kind: RedirectKind) -> Result<(), NamedRoutesError> {
+        let follows_with_get = matches!(
+            kind,
+            RedirectKind::Found | RedirectKind::SeeOther | RedirectKind::Permanent
+        );
+
+        if follows_with_get && !accepts_method(&self.methods, &Method::GET) {
+            return Err(NamedRoutesError::MethodNotAllowed {
+                route: self.template.raw().to_string(),
+                method: Method::GET.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Adds the flash messages' cookie to the headers
+    pub(crate) fn append_flash(&self, headers: &mut HeaderMap) -> Result<(), NamedRoutesError> {
+        if !self.flash.is_empty() {
+            let cookie = flash::set_cookie(
+                &self.settings.signing_keys,
+                &self.flash,
+                self.settings.secure_cookies(),
+            )?;
+            headers.append(header::SET_COOKIE, cookie);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn location(&self) -> Result<header::HeaderValue, NamedRoutesError> {
+        let path = if self.absolute {
+            self.url()?.to_string()
+        } else {
+            self.path()
+        };
+        header::HeaderValue::from_str(path.as_str())
+            .map_err(|_| NamedRoutesError::InvalidHeaderValue(path))
+    }
+
+    /// Returns the build route's path. The encoded length is measured first so
+    /// that the path is written into a buffer of the right size
     pub fn path(&self) -> String {
-        let mut raw = self.raw.clone();
-        if self.parts.is_some() {
-            for (k, v) in self.parts.as_ref().unwrap().iter() {
-                let name = if k.starts_with('{') && k.ends_with('}') {
-                    k
-                } else {
-                    &format!("{{{}}}", k)
-                };
-                raw = raw.replace(name, v);
+        let mut len = WrittenLen(0);
+        // Neither writer fails
+        let _ = self.write_path(&mut len);
+        let mut path = String::with_capacity(len.0);
+        let _ = self.write_path(&mut path);
+        path
+    }
+
+    /// Writes the build route's path, including the query string, to `out`.
+    /// Parameters without a value are written as they appear in the template
+    pub fn write_path<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        for segment in self.template.segments() {
+            match segment {
+                Segment::Literal(literal) => out.write_str(literal)?,
+                Segment::Parameter { index, catch_all } => {
+                    let name = self.template.parameter(*index);
+                    let value = self.parts.as_ref().and_then(|parts| parts.get(name));
+
+                    match value {
+                        Some(value) => self.write_value(out, name, value, *catch_all)?,
+                        None => self.template.write_placeholder(out, *index, *catch_all)?,
+                    }
+                }
             }
         }
 
-        raw
+        if !self.query.is_empty() {
+            out.write_char('?')?;
+            self.query.write_query_string(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the absolute URL built with the registry's base URL
+    pub fn url(&self) -> Result<Uri, NamedRoutesError> {
+        match &self.settings.base_url {
+            Some(base_url) => self.url_with_base(base_url),
+            None => Err(NamedRoutesError::MissingBaseUrl),
+        }
+    }
+
+    /// Returns the absolute URL built with the given base URL
+    pub fn url_with_base(&self, base_url: &BaseUrl) -> Result<Uri, NamedRoutesError> {
+        base_url.join(&self.path())
+    }
+
+    fn write_value<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        name: &str,
+        value: &str,
+        catch_all: bool,
+    ) -> fmt::Result {
+        if self.pre_encoded || self.encoded.contains(name) {
+            out.write_str(value)
+        } else if catch_all {
+            write_path_segments(out, value)
+        } else {
+            write_path_segment(out, value)
+        }
+    }
+}
+
+/// Counts the bytes written to it
+struct WrittenLen(usize);
+
+impl fmt::Write for WrittenLen {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Redirector;
+    use super::{RedirectKind, Redirector};
+    use crate::{registry::RouteSettings, BaseUrl, NamedRoutesError};
+    use axum::http::{header, StatusCode};
     use std::collections::HashMap;
 
     #[test]
@@ -118,4 +445,191 @@ mod test {
         let redirector = Redirector::new("/user/{id}/{id}", Some(parts));
         assert_eq!(redirector.path().as_str(), "/user/1234/1234");
     }
+
+    #[test]
+    fn test_redirect_sets_location() {
+        let redirector = Redirector::new("/home", None);
+        let response = redirector.try_empty_redirect().unwrap();
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.headers().get(header::LOCATION).unwrap(), "/home");
+    }
+
+    #[test]
+    fn test_invalid_header_value() {
+        let mut parts = HashMap::new();
+        parts.insert("name".to_string(), "a\nb".to_string());
+
+        let redirector = Redirector::new("/{name}", Some(parts)).pre_encoded();
+        assert_eq!(
+            redirector.try_redirect_t(()).err(),
+            Some(NamedRoutesError::InvalidHeaderValue("/a\nb".to_string()))
+        );
+        assert_eq!(
+            redirector.redirect(()).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_path_values_are_encoded() {
+        let mut parts = HashMap::new();
+        parts.insert("name".to_string(), "a b/c?d".to_string());
+
+        let redirector = Redirector::new("/user/{name}", Some(parts));
+        assert_eq!(redirector.path().as_str(), "/user/a%20b%2Fc%3Fd");
+        assert_eq!(
+            redirector.clone().pre_encoded().path().as_str(),
+            "/user/a b/c?d"
+        );
+    }
+
+    #[test]
+    fn test_path_is_built_in_one_buffer() {
+        let mut parts = HashMap::new();
+        parts.insert("id".to_string(), "1".to_string());
+        let path = Redirector::new("/users/{id}", Some(parts))
+            .with_query([("page", "2"), ("sort", "name")])
+            .path();
+
+        assert_eq!(path, "/users/1?page=2&sort=name");
+        assert_eq!(path.capacity(), path.len());
+    }
+
+    #[test]
+    fn test_path_buffer_fits_encoded_and_repeated_values() {
+        let mut parts = HashMap::new();
+        parts.insert("id".to_string(), "a b/é".to_string());
+        parts.insert("unused".to_string(), "x".repeat(100));
+        let path = Redirector::new("/users/{id}/copy/{id}", Some(parts))
+            .with_query([("q", "a b")])
+            .path();
+
+        assert_eq!(path, "/users/a%20b%2F%C3%A9/copy/a%20b%2F%C3%A9?q=a+b");
+        assert_eq!(path.capacity(), path.len());
+    }
+
+    async fn round_trip(template: &str, value: &str) -> String {
+        use axum::{body::Body, extract::Path, http::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        let mut parts = HashMap::new();
+        parts.insert("name".to_string(), value.to_string());
+        let path = Redirector::new(template, Some(parts)).path();
+
+        let app = Router::new().route(
+            template,
+            get(|Path(name): Path<String>| async move { name }),
+        );
+        let response = app
+            .oneshot(Request::builder().uri(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        crate::test_support::body_string(response).await
+    }
+
+    #[tokio::test]
+    async fn test_encoded_path_round_trips_through_axum() {
+        let value = "a b/c?d#e%f+g é";
+        assert_eq!(round_trip("/user/{name}", value).await, value);
+    }
+
+    #[tokio::test]
+    async fn test_encoded_catch_all_round_trips_through_axum() {
+        let value = "docs/a b?c/d#e.txt";
+        assert_eq!(round_trip("/files/{*name}", value).await, value);
+    }
+
+    #[test]
+    fn test_path_with_a_catch_all_part() {
+        let mut parts = HashMap::new();
+        parts.insert("path".to_string(), "docs/a b.txt".to_string());
+        let redirector = Redirector::new("/files/{*path}", Some(parts));
+        assert_eq!(redirector.path().as_str(), "/files/docs/a%20b.txt");
+
+        let mut parts = HashMap::new();
+        parts.insert("{*path}".to_string(), "docs/a?b".to_string());
+        let redirector = Redirector::new("/files/{*path}", Some(parts));
+        assert_eq!(redirector.path().as_str(), "/files/docs/a%3Fb");
+    }
+
+    #[test]
+    fn test_path_with_query() {
+        let mut parts = HashMap::new();
+        parts.insert("id".to_string(), "1".to_string());
+
+        let redirector = Redirector::new("/user/{id}", Some(parts))
+            .with_query(("tab", "posts"))
+            .with_query(vec![("page", "2")]);
+        assert_eq!(redirector.path().as_str(), "/user/1?page=2&tab=posts");
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let mut parts = HashMap::new();
+        parts.insert("id".to_string(), "1".to_string());
+        let redirector = Redirector::new("/user/{id}", Some(parts));
+
+        assert_eq!(redirector.url(), Err(NamedRoutesError::MissingBaseUrl));
+
+        let redirector = redirector.with_settings(RouteSettings {
+            base_url: Some(BaseUrl::new("https", "example.com")),
+            ..Default::default()
+        });
+        assert_eq!(
+            redirector.url().unwrap().to_string(),
+            "https://example.com/user/1"
+        );
+        assert_eq!(
+            redirector
+                .url_with_base(&BaseUrl::new("http", "localhost").port(3000))
+                .unwrap()
+                .to_string(),
+            "http://localhost:3000/user/1"
+        );
+    }
+
+    #[test]
+    fn test_write_path() {
+        let mut parts = HashMap::new();
+        parts.insert("{id}".to_string(), "a b".to_string());
+        let redirector = Redirector::new("/user/{id}/{tab}", Some(parts)).with_query(("page", "2"));
+
+        let mut out = String::from("link: ");
+        redirector.write_path(&mut out).unwrap();
+
+        assert_eq!(out, "link: /user/a%20b/{tab}?page=2");
+    }
+
+    #[test]
+    fn test_redirect_kinds() {
+        let redirector = Redirector::new("/home", None);
+
+        assert_eq!(redirector.see_other().status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            redirector.temporary().status(),
+            StatusCode::TEMPORARY_REDIRECT
+        );
+        assert_eq!(
+            redirector.permanent().status(),
+            StatusCode::MOVED_PERMANENTLY
+        );
+        assert_eq!(
+            redirector.permanent_redirect().status(),
+            StatusCode::PERMANENT_REDIRECT
+        );
+        assert_eq!(
+            redirector
+                .see_other()
+                .headers()
+                .get(header::LOCATION)
+                .unwrap(),
+            "/home"
+        );
+
+        let redirector = redirector.with_kind(RedirectKind::SeeOther);
+        assert_eq!(redirector.redirect(()).status(), StatusCode::SEE_OTHER);
+        assert_eq!(redirector.redirect_t(()).status(), StatusCode::SEE_OTHER);
+    }
 }
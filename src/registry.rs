@@ -0,0 +1,699 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, RwLock},
+};
+
+use axum::http::{request::Parts, Method};
+
+use crate::{
+    signed_url::SigningKeys, BaseUrl, NamedRoutesService, RedirectKind, RepoInner, RouteMeta,
+    RoutePath, TrustedProxies, NAME_ROUTES_REPO,
+};
+
+/// A collection of named routes
+///
+/// By default every `RouterWrapper` and `NamedRoutesService` share the
+/// process wide registry. Create a new registry when two applications live
+/// in the same process and their route names must not clash.
+///
+/// ```rust
+///  use named_routes_axum::{RouteRegistry, RouterWrapper};
+///
+/// let registry = RouteRegistry::new();
+///
+/// let admin = RouterWrapper::<()>::with_registry(registry.clone())
+///     .get("/", || async { "Admin" }, "home");
+///
+/// assert_eq!(registry.service().get_path("home"), Some("/".to_string()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RouteRegistry {
+    routes: RepoInner,
+    state: Arc<RwLock<RegistryState>>,
+}
+
+/// What happens when a name is registered a second time for a different path
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The new path replaces the existing one
+    #[default]
+    Overwrite,
+    /// The new path replaces the existing one and a warning is logged with `tracing`
+    Warn,
+    /// Registration panics
+    Reject,
+    /// The existing path is kept and the collision is reported by
+    /// `RouterWrapper::try_into_router`
+    Error,
+}
+
+/// A name that was registered for two different paths
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRoute {
+    pub name: String,
+    pub existing: String,
+    pub new: String,
+}
+
+impl Display for DuplicateRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "route name `{}` is registered for `{}` and `{}`",
+            self.name, self.existing, self.new
+        )
+    }
+}
+
+/// Registry wide settings that are carried by each `RoutePath` and `Redirector`
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RouteSettings {
+    pub(crate) base_url: Option<BaseUrl>,
+    pub(crate) redirect_kind: RedirectKind,
+    pub(crate) signing_keys: SigningKeys,
+    pub(crate) secure_cookies: Option<bool>,
+}
+
+impl RouteSettings {
+    /// Whether cookies get the `Secure` attribute. Unless it was set, only
+    /// when the base URL is missing or uses https
+    pub(crate) fn secure_cookies(&self) -> bool {
+        self.secure_cookies.unwrap_or_else(|| {
+            self.base_url
+                .as_ref()
+                .is_none_or(|base_url| base_url.scheme_str() == "https")
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    duplicate_policy: DuplicatePolicy,
+    duplicates: Vec<DuplicateRoute>,
+    settings: RouteSettings,
+    trusted_proxies: TrustedProxies,
+    allowed_hosts: Vec<String>,
+    meta: HashMap<String, RouteMeta>,
+}
+
+impl Default for RouteRegistry {
+    fn default() -> Self {
+        Self {
+            routes: Arc::new(RwLock::new(HashMap::new())),
+            state: Default::default(),
+        }
+    }
+}
+
+impl RouteRegistry {
+    /// Creates a new, empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the process wide registry
+    pub fn global() -> Self {
+        NAME_ROUTES_REPO.get_or_init(Self::new).clone()
+    }
+
+    /// Returns the registry added to the request by `RouterWrapper`, or the
+    /// process wide registry when there is none
+    pub fn from_parts(parts: &Parts) -> Self {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .unwrap_or_else(Self::global)
+    }
+
+    /// Returns a service that resolves names against this registry
+    pub fn service(&self) -> NamedRoutesService {
+        NamedRoutesService::with_registry(self)
+    }
+
+    /// Returns true when both values point to the same registry
+    pub fn same_as(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.routes, &other.routes)
+    }
+
+    /// Sets what happens when a name is registered for a second path
+    pub fn set_duplicate_policy(&self, policy: DuplicatePolicy) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.duplicate_policy = policy;
+        }
+
+        self
+    }
+
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.state
+            .read()
+            .map(|state| state.duplicate_policy)
+            .unwrap_or_default()
+    }
+
+    /// Returns every name that has been registered for more than one path
+    /// since the duplicates were last taken
+    pub fn duplicates(&self) -> Vec<DuplicateRoute> {
+        self.state
+            .read()
+            .map(|state| state.duplicates.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the duplicates and clears the list, so that they are reported once
+    pub fn take_duplicates(&self) -> Vec<DuplicateRoute> {
+        self.state
+            .write()
+            .map(|mut state| std::mem::take(&mut state.duplicates))
+            .unwrap_or_default()
+    }
+
+    /// Sets the base used to build absolute URLs
+    pub fn set_base_url(&self, base_url: BaseUrl) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.settings.base_url = Some(base_url);
+        }
+
+        self
+    }
+
+    pub fn base_url(&self) -> Option<BaseUrl> {
+        self.state
+            .read()
+            .ok()
+            .and_then(|state| state.settings.base_url.clone())
+    }
+
+    /// Sets the status code used by `redirect` on routes of this registry
+    pub fn set_redirect_kind(&self, kind: RedirectKind) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.settings.redirect_kind = kind;
+        }
+
+        self
+    }
+
+    pub fn redirect_kind(&self) -> RedirectKind {
+        self.state
+            .read()
+            .map(|state| state.settings.redirect_kind)
+            .unwrap_or_default()
+    }
+
+    /// Sets the proxies whose forwarded headers are used by `UrlGenerator`
+    pub fn set_trusted_proxies(&self, proxies: TrustedProxies) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.trusted_proxies = proxies;
+        }
+
+        self
+    }
+
+    pub fn trusted_proxies(&self) -> TrustedProxies {
+        self.state
+            .read()
+            .map(|state| state.trusted_proxies.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets the hosts, besides the base URL's, that `resolve_safe_target` accepts.
+    /// A host without a port accepts any port
+    pub fn set_allowed_hosts<I, H>(&self, hosts: I) -> &Self
+    where
+        I: IntoIterator<Item = H>,
+        H: ToString,
+    {
+        if let Ok(mut state) = self.state.write() {
+            state.allowed_hosts = hosts.into_iter().map(|h| h.to_string()).collect();
+        }
+
+        self
+    }
+
+    pub fn allowed_hosts(&self) -> Vec<String> {
+        self.state
+            .read()
+            .map(|state| state.allowed_hosts.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets the keys used to sign URLs. The first key signs new URLs and every
+    /// key is accepted when verifying, so old keys can be kept while rotating
+    pub fn set_signing_keys<I, K>(&self, keys: I) -> &Self
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        if let Ok(mut state) = self.state.write() {
+            state.settings.signing_keys = SigningKeys::new(keys);
+        }
+
+        self
+    }
+
+    /// Sets whether the flash cookie gets the `Secure` attribute, which browsers
+    /// need to send it back over plain http. By default it is set unless the
+    /// base URL uses http
+    pub fn set_secure_cookies(&self, secure: bool) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.settings.secure_cookies = Some(secure);
+        }
+
+        self
+    }
+
+    pub(crate) fn signing_keys(&self) -> SigningKeys {
+        self.state
+            .read()
+            .map(|state| state.settings.signing_keys.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn settings(&self) -> RouteSettings {
+        self.state
+            .read()
+            .map(|state| state.settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets the metadata of the route, replacing any metadata it had
+    pub fn set_meta(&self, name: &str, meta: RouteMeta) -> &Self {
+        if let Ok(mut state) = self.state.write() {
+            state.meta.insert(name.to_string(), meta);
+        }
+
+        self
+    }
+
+    pub fn meta(&self, name: &str) -> Option<RouteMeta> {
+        self.state
+            .read()
+            .ok()
+            .and_then(|state| state.meta.get(name).cloned())
+    }
+
+    /// Returns the names of the routes whose metadata has the tag
+    pub(crate) fn names_tagged(&self, tag: &str) -> Vec<String> {
+        self.state
+            .read()
+            .map(|state| {
+                state
+                    .meta
+                    .iter()
+                    .filter(|(_, meta)| meta.has_tag(tag))
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Registers `path` under `name`, applying the duplicate policy.
+    /// Registering the same name for the same path again is not a duplicate
+    pub fn register(&self, name: &str, path: &str) -> &Self {
+        self.register_methods(name, path, &[])
+    }
+
+    /// Registers `path` under `name` for the given HTTP methods.
+    /// Registering the same name and path again adds the methods to the route
+    pub fn register_methods(&self, name: &str, path: &str, methods: &[Method]) -> &Self {
+        let policy = self.duplicate_policy();
+
+        if let Ok(mut write_lock) = self.routes.write() {
+            if let Some(existing) = write_lock.get_mut(name) {
+                if existing.template() == path {
+                    existing.add_methods(methods);
+                    return self;
+                }
+
+                let duplicate = DuplicateRoute {
+                    name: name.to_string(),
+                    existing: existing.template().to_string(),
+                    new: path.to_string(),
+                };
+
+                match policy {
+                    DuplicatePolicy::Overwrite => (),
+                    DuplicatePolicy::Warn => tracing::warn!("{}", &duplicate),
+                    DuplicatePolicy::Reject => {
+                        drop(write_lock);
+                        panic!("{}", &duplicate)
+                    }
+                    DuplicatePolicy::Error => (),
+                }
+
+                if let Ok(mut state) = self.state.write() {
+                    state.duplicates.push(duplicate);
+                }
+
+                if policy == DuplicatePolicy::Error {
+                    return self;
+                }
+            }
+
+            let mut route = RoutePath::from(path);
+            route.add_methods(methods);
+            write_lock.insert(name.to_string(), route);
+        }
+
+        self
+    }
+
+    /// Copies the given routes of `other`, with their metadata, into this registry.
+    /// `prefix` is prepended to each of the copied paths
+    pub fn import<N: AsRef<str>>(&self, other: &Self, names: &[N], prefix: Option<&str>) -> &Self {
+        if self.same_as(other) {
+            return self;
+        }
+
+        let routes = other.all();
+        for name in names.iter().map(AsRef::as_ref) {
+            let Some(route) = routes.get(name) else {
+                continue;
+            };
+
+            match prefix {
+                Some(prefix) => self.register_methods(
+                    name,
+                    &format!("{}{}", prefix, route.template()),
+                    route.methods(),
+                ),
+                None => self.register_methods(name, route.template(), route.methods()),
+            };
+
+            if let Some(meta) = other.meta(name) {
+                self.set_meta(name, meta);
+            }
+        }
+
+        self
+    }
+
+    /// Returns the name registered for the template and method, when there is exactly one
+    pub(crate) fn name_of(&self, template: &str, method: &Method) -> Option<String> {
+        let read = self.routes.read().ok()?;
+        let mut names = read
+            .iter()
+            .filter(|(_, route)| route.template() == template && route.accepts(method))
+            .map(|(name, _)| name);
+
+        match (names.next(), names.next()) {
+            (Some(name), None) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn routes(&self) -> &RepoInner {
+        &self.routes
+    }
+
+    pub(crate) fn all(&self) -> HashMap<String, RoutePath> {
+        if let Ok(read) = self.routes.read() {
+            read.clone()
+        } else {
+            HashMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::{Method, StatusCode};
+
+    use super::{DuplicatePolicy, DuplicateRoute, RouteRegistry};
+    use crate::{NamedRoutesError, NamedRoutesRepo, RedirectKind, RouteMeta, RouterWrapper};
+
+    #[test]
+    fn test_registries_are_isolated() {
+        let first = RouteRegistry::new();
+        let second = RouteRegistry::new();
+
+        NamedRoutesRepo::with_registry(&first, None).register("home", "/first");
+        NamedRoutesRepo::with_registry(&second, None).register("home", "/second");
+
+        assert_eq!(first.service().get_path("home"), Some("/first".to_string()));
+        assert_eq!(
+            second.service().get_path("home"),
+            Some("/second".to_string())
+        );
+        assert_eq!(first.all().len(), 1);
+    }
+
+    #[test]
+    fn test_global_registry_is_shared() {
+        assert!(RouteRegistry::global().same_as(&RouteRegistry::global()));
+        assert!(!RouteRegistry::new().same_as(&RouteRegistry::global()));
+    }
+
+    #[test]
+    fn test_wrapper_registers_into_its_registry() {
+        let registry = RouteRegistry::new();
+
+        let _ = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/", || async { "home" }, "registry-home")
+            .nest_given("/admin", |router| {
+                router.get("/users", || async { "users" }, "registry-users")
+            })
+            .merge_given(|router| router.get("/about", || async { "about" }, "registry-about"));
+
+        let service = registry.service();
+        assert_eq!(service.get_path("registry-home"), Some("/".to_string()));
+        assert_eq!(
+            service.get_path("registry-users"),
+            Some("/admin/users".to_string())
+        );
+        assert_eq!(
+            service.get_path("registry-about"),
+            Some("/about".to_string())
+        );
+        assert!(!RouteRegistry::global().service().has("registry-home"));
+    }
+
+    #[test]
+    fn test_merging_a_wrapper_from_another_registry() {
+        let parent = RouteRegistry::new();
+        let child = RouteRegistry::new();
+
+        let _ = RouterWrapper::<()>::with_registry(parent.clone())
+            .get("/", || async { "home" }, "home")
+            .merge(RouterWrapper::with_registry(child.clone()).get(
+                "/two",
+                || async { "two" },
+                "two",
+            ))
+            .nest(
+                "/nested",
+                RouterWrapper::with_registry(RouteRegistry::new()).get(
+                    "/three",
+                    || async { "three" },
+                    "three",
+                ),
+            );
+
+        let service = parent.service();
+        assert_eq!(service.get_path("two"), Some("/two".to_string()));
+        assert_eq!(service.get_path("three"), Some("/nested/three".to_string()));
+        assert!(!child.service().has("home"));
+    }
+
+    #[test]
+    fn test_only_the_wrapper_names_are_imported() {
+        let registry = RouteRegistry::new();
+        RouteRegistry::global().register("registry.other.app", "/other-app");
+
+        let _ = RouterWrapper::<()>::with_registry(registry.clone()).nest(
+            "/api",
+            RouterWrapper::new().merge_given(|router| {
+                router.get("/ping", || async { "pong" }, "registry.api.ping")
+            }),
+        );
+
+        let service = registry.service();
+        assert_eq!(
+            service.get_path("registry.api.ping"),
+            Some("/api/ping".to_string())
+        );
+        assert!(!service.has("registry.other.app"));
+        assert_eq!(service.all().len(), 1);
+    }
+
+    #[test]
+    fn test_same_path_is_not_a_duplicate() {
+        let registry = RouteRegistry::new();
+        registry.set_duplicate_policy(DuplicatePolicy::Reject);
+
+        let _ = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/users", || async { "list" }, "users")
+            .post("/users", || async { "create" }, "users");
+
+        assert!(registry.duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_overwrite_policy_reports_duplicates() {
+        let registry = RouteRegistry::new();
+        registry.register("home", "/").register("home", "/home");
+
+        assert_eq!(
+            registry.service().get_path("home"),
+            Some("/home".to_string())
+        );
+        assert_eq!(
+            registry.duplicates(),
+            vec![DuplicateRoute {
+                name: "home".to_string(),
+                existing: "/".to_string(),
+                new: "/home".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "route name `home` is registered for `/` and `/home`")]
+    fn test_reject_policy_panics() {
+        let registry = RouteRegistry::new();
+        registry.set_duplicate_policy(DuplicatePolicy::Reject);
+
+        let _ = RouterWrapper::<()>::with_registry(registry)
+            .get("/", || async { "home" }, "home")
+            .merge_given(|router| router.get("/home", || async { "home" }, "home"));
+    }
+
+    #[test]
+    fn test_error_policy_keeps_the_first_path() {
+        let registry = RouteRegistry::new();
+        registry.set_duplicate_policy(DuplicatePolicy::Error);
+
+        let result = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/", || async { "home" }, "home")
+            .merge(RouterWrapper::with_registry(RouteRegistry::new()).get(
+                "/home",
+                || async { "home" },
+                "home",
+            ))
+            .try_into_router();
+
+        assert_eq!(registry.service().get_path("home"), Some("/".to_string()));
+        assert_eq!(result.err().map(|list| list.len()), Some(1));
+        assert!(registry.duplicates().is_empty());
+
+        let result = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/about", || async { "about" }, "about")
+            .try_into_router();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_registry_redirect_kind() {
+        let registry = RouteRegistry::new();
+        registry
+            .register("login", "/login")
+            .set_redirect_kind(RedirectKind::SeeOther);
+
+        let route = registry.service().try_get("login").unwrap();
+        assert_eq!(route.redirect(()).status(), StatusCode::SEE_OTHER);
+        assert_eq!(route.temporary().status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            route
+                .try_redirect_as(RedirectKind::Found, ())
+                .unwrap()
+                .status(),
+            StatusCode::FOUND
+        );
+    }
+
+    #[test]
+    fn test_methods_are_recorded_per_name() {
+        let registry = RouteRegistry::new();
+
+        let _ = RouterWrapper::<()>::with_registry(registry.clone())
+            .get("/users", || async { "list" }, "users")
+            .post("/users", || async { "create" }, "users")
+            .post("/users/{id}/delete", || async { "deleted" }, "users.delete")
+            .any_of(
+                &["put", "PATCH"],
+                "/users/{id}",
+                || async { "update" },
+                "users.update",
+            )
+            .nest_given("/admin", |router| {
+                router.delete("/users/{id}", || async { "deleted" }, "admin.users.delete")
+            });
+
+        let service = registry.service();
+        assert_eq!(
+            service.methods("users"),
+            Some(vec![Method::GET, Method::POST])
+        );
+        assert_eq!(
+            service.methods("users.update"),
+            Some(vec![Method::PUT, Method::PATCH])
+        );
+        assert_eq!(
+            service.methods("admin.users.delete"),
+            Some(vec![Method::DELETE])
+        );
+
+        let route = service.try_get("users.delete").unwrap();
+        assert!(!route.accepts(&Method::GET));
+        assert_eq!(
+            route.with((1,)).try_redirect(()).err(),
+            Some(NamedRoutesError::MethodNotAllowed {
+                route: "/users/{id}/delete".to_string(),
+                method: "GET".to_string(),
+            })
+        );
+        assert_eq!(
+            route
+                .with((1,))
+                .try_redirect_as(RedirectKind::Temporary, ())
+                .unwrap()
+                .status(),
+            StatusCode::TEMPORARY_REDIRECT
+        );
+        assert!(service.try_get("users").unwrap().try_redirect(()).is_ok());
+        assert_eq!(
+            route.with((1,)).redirect(()).status(),
+            StatusCode::FOUND,
+            "the lenient redirect only logs the method"
+        );
+
+        assert_eq!(
+            service
+                .recognize_method("/users/1", &Method::PATCH)
+                .map(|found| found.name),
+            Some("users.update".to_string())
+        );
+        assert_eq!(service.recognize_method("/users/1", &Method::GET), None);
+    }
+
+    #[test]
+    fn test_meta_is_kept_when_merging() {
+        let registry = RouteRegistry::new();
+
+        let _ = RouterWrapper::<()>::with_registry(registry.clone())
+            .meta(RouteMeta::new().with_tag("ignored"))
+            .get("/", || async { "home" }, "home")
+            .meta(RouteMeta::new().with_tag("public"))
+            .merge(
+                RouterWrapper::with_registry(RouteRegistry::new())
+                    .get("/admin", || async { "admin" }, "admin")
+                    .meta(RouteMeta::new().with_tag("admin").with_owner("ops")),
+            );
+
+        let service = registry.service();
+        assert_eq!(
+            service.routes_tagged("admin").keys().collect::<Vec<_>>(),
+            vec!["admin"]
+        );
+        assert_eq!(
+            service
+                .meta("admin")
+                .and_then(|meta| meta.owner().map(String::from)),
+            Some("ops".to_string())
+        );
+        assert!(service.routes_tagged("public").contains_key("home"));
+        assert!(service.routes_tagged("ignored").is_empty());
+    }
+}
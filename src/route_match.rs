@@ -0,0 +1,21 @@
+use std::collections::HashMap;
+
+use axum::http::Method;
+
+/// The route a path belongs to. Returned by `NamedRoutesService::recognize`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteMatch {
+    /// The name of the matched route
+    pub name: String,
+    /// The percent-decoded parameter values, keyed by parameter name
+    pub params: HashMap<String, String>,
+    /// The HTTP methods the route was registered for, empty when they are not known
+    pub methods: Vec<Method>,
+}
+
+impl RouteMatch {
+    /// Returns the value of a parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use axum::http::Extensions;
+
+/// Facts about a named route, e.g. for route listings, OpenAPI output or authorization.
+///
+/// ```rust
+///  use named_routes_axum::{RouteMeta, RouteRegistry, RouterWrapper};
+///
+/// let registry = RouteRegistry::new();
+/// let _ = RouterWrapper::<()>::with_registry(registry.clone())
+///     .get("/admin/users", || async { "users" }, "admin.users")
+///     .meta(RouteMeta::new().with_description("List users").with_tag("admin"));
+///
+/// let service = registry.service();
+/// assert!(service.routes_tagged("admin").contains_key("admin.users"));
+/// assert_eq!(service.meta("admin.users").unwrap().description(), Some("List users"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RouteMeta {
+    description: Option<String>,
+    tags: Vec<String>,
+    owner: Option<String>,
+    auth: Option<String>,
+    deprecated: bool,
+    values: BTreeMap<String, String>,
+    extensions: Extensions,
+}
+
+impl RouteMeta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    /// The team or person that owns the route
+    pub fn with_owner(mut self, owner: &str) -> Self {
+        self.owner = Some(owner.to_string());
+        self
+    }
+
+    /// The authorization the route requires, e.g. a role or a scope
+    pub fn with_auth(mut self, requirement: &str) -> Self {
+        self.auth = Some(requirement.to_string());
+        self
+    }
+
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Adds a key and value
+    pub fn with_value(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds a typed value, read back with `get`. There is one value per type
+    pub fn with_extension<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn auth(&self) -> Option<&str> {
+        self.auth.as_deref()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    pub fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    /// Returns the typed value added with `with_extension`
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<T>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RouteMeta;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct RateLimit(u32);
+
+    #[test]
+    fn test_route_meta() {
+        let meta = RouteMeta::new()
+            .with_description("Show a user")
+            .with_tag("users")
+            .with_tag("users")
+            .with_tag("public")
+            .with_owner("accounts")
+            .with_auth("users:read")
+            .deprecated()
+            .with_value("since", "1.2")
+            .with_extension(RateLimit(10));
+
+        assert_eq!(meta.description(), Some("Show a user"));
+        assert_eq!(meta.tags(), &["users", "public"]);
+        assert_eq!(meta.owner(), Some("accounts"));
+        assert_eq!(meta.auth(), Some("users:read"));
+        assert!(meta.is_deprecated());
+        assert_eq!(meta.value("since"), Some("1.2"));
+        assert_eq!(meta.get::<RateLimit>(), Some(&RateLimit(10)));
+        assert_eq!(meta.get::<u32>(), None);
+    }
+}
@@ -1,21 +1,86 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use axum::{
     body::Body,
+    http::Method,
     response::{IntoResponse, Response},
 };
 
-use crate::redirector::Redirector;
+use crate::{
+    encoding::write_path_segment,
+    redirector::{RedirectKind, Redirector},
+    registry::RouteSettings,
+    template::Template,
+    NamedRoutesError,
+};
 
 #[derive(Debug, Default, Clone)]
 pub struct RoutePath {
-    raw: String,
-    has_parts: bool,
+    template: Template,
+    methods: Vec<Method>,
+    settings: RouteSettings,
 }
 
+/// A list of path segments for a catch-all parameter such as `{*path}`.
+/// Each segment is encoded on its own, including any `/` it contains, and
+/// the segments are joined with `/`
+///
+/// ```rust
+///  use std::collections::HashMap;
+///  use named_routes_axum::{RoutePath, Segments};
+///
+/// let route = RoutePath::from("/files/{*path}");
+///
+/// assert_eq!(route.with(Segments::from(vec!["docs", "a b.txt"])).path(), "/files/docs/a%20b.txt");
+/// assert_eq!(route.with(Segments::from(vec!["a/b", "c"])).path(), "/files/a%2Fb/c");
+///
+/// let by_name = HashMap::from([("path", Segments::from(vec!["a/b", "c"]))]);
+/// assert_eq!(route.with(by_name).path(), "/files/a%2Fb/c");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Segments(Vec<String>);
+
+impl Segments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<V: ToString>(mut self, segment: V) -> Self {
+        self.0.push(segment.to_string());
+        self
+    }
+}
+
+impl<V: ToString> From<Vec<V>> for Segments {
+    fn from(value: Vec<V>) -> Self {
+        Self(value.into_iter().map(|v| v.to_string()).collect())
+    }
+}
+
+impl Segments {
+    /// Returns the segments percent-encoded and joined with `/`
+    fn encode(&self) -> String {
+        let mut out = String::new();
+        for (pos, segment) in self.0.iter().enumerate() {
+            if pos > 0 {
+                out.push('/');
+            }
+            let _ = write_path_segment(&mut out, segment);
+        }
+
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct PartsValue {
     pos: Option<BTreeMap<usize, String>>,
     name: Option<HashMap<String, String>>,
+    error: Option<NamedRoutesError>,
+    /// Positions whose values are already percent-encoded
+    encoded: BTreeSet<usize>,
+    /// Names whose values are already percent-encoded
+    encoded_names: BTreeSet<String>,
 }
 
 impl PartsValue {
@@ -24,13 +89,36 @@ impl PartsValue {
         let Self {
             pos: left_pos,
             name: left_name,
+            error: left_error,
+            encoded: left_encoded,
+            encoded_names: left_encoded_names,
         } = self;
 
         let Self {
             pos: right_pos,
             name: right_name,
+            error: right_error,
+            encoded: right_encoded,
+            encoded_names: right_encoded_names,
         } = other;
 
+        let encoded = left_encoded
+            .iter()
+            .filter(|key| right_pos.as_ref().is_none_or(|pos| !pos.contains_key(key)))
+            .chain(right_encoded)
+            .copied()
+            .collect();
+        let encoded_names = left_encoded_names
+            .iter()
+            .filter(|key| {
+                right_name
+                    .as_ref()
+                    .is_none_or(|name| !name.contains_key(*key))
+            })
+            .chain(right_encoded_names)
+            .cloned()
+            .collect();
+
         let pos = match (left_pos, right_pos) {
             (None, None) => None,
             (None, Some(pos)) => Some(pos.clone()),
@@ -59,55 +147,222 @@ impl PartsValue {
             }
         };
 
-        Self { pos, name }
+        Self {
+            pos,
+            name,
+            error: left_error.clone().or_else(|| right_error.clone()),
+            encoded,
+            encoded_names,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl PartsValue {
+    /// Creates named parts from a struct or a map. Field names are matched
+    /// with the path's `{placeholders}`, fields that are `None` are left out
+    pub fn try_from_serialize<T: serde::Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<Self, NamedRoutesError> {
+        Ok(Self {
+            pos: None,
+            name: Some(crate::serde_parts::to_named_parts(value)?),
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
+        })
+    }
+}
+
+/// Nested or unsupported values are reported by `RoutePath::try_with`,
+/// `RoutePath::with` panics
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + ?Sized> From<crate::Serialized<'_, T>> for PartsValue {
+    fn from(value: crate::Serialized<'_, T>) -> Self {
+        Self::try_from_serialize(value.0).unwrap_or_else(|error| Self {
+            pos: None,
+            name: Some(HashMap::new()),
+            error: Some(error),
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
+        })
     }
 }
 
 impl RoutePath {
+    /// Fills the path's parameters with the given values.
+    ///
+    /// Named values fill the parameters with the same name and positional
+    /// values fill the remaining parameters in order. Missing positional
+    /// values are replaced with an empty string and extra values are ignored.
+    /// Use `try_with` to have the values validated
+    ///
+    /// # Panics
+    ///
+    /// When the values could not be converted, e.g. a `Serialized` value that
+    /// is not a struct or a map
     pub fn with<P: Into<PartsValue>>(&self, values: P) -> Redirector {
         let parts = values.into();
-        if parts.pos.is_some() {
-            let map = parts.pos.unwrap();
-            let mut named_map = HashMap::new();
-            for (pos, name) in self
-                .raw
-                .split('/')
-                .filter(|p| p.starts_with('{') && p.ends_with('}'))
-                .enumerate()
-            {
-                let value = if let Some(v) = map.get(&pos) {
-                    v.clone()
-                } else {
-                    "".to_string()
-                };
-                named_map.insert(name.to_string(), value);
+        if let Some(error) = parts.error {
+            panic!("{}", error);
+        }
+        let has_pos = parts.pos.is_some();
+        let resolved = self.resolve(parts);
+
+        let mut named_map = resolved.values;
+        let encoded = resolved.encoded;
+        if has_pos {
+            for name in resolved.missing {
+                named_map.insert(name, "".to_string());
             }
+        }
 
-            self.make_redirector(Some(named_map))
-        } else {
-            self.make_redirector(Some(
-                parts
-                    .name
-                    .unwrap()
-                    .into_iter()
-                    .map(|(k, v)| (format!("{{{}}}", k), v.to_string()))
-                    .collect(),
-            ))
+        self.make_redirector(Some(named_map)).with_encoded(encoded)
+    }
+
+    /// Fills the path's parameters with the given values.
+    ///
+    /// Every parameter must be given a non empty value and every value must
+    /// belong to a parameter. A positional value that is left over because
+    /// its parameter was given by name is reported as a conflict
+    pub fn try_with<P: Into<PartsValue>>(&self, values: P) -> Result<Redirector, NamedRoutesError> {
+        let parts = values.into();
+        if let Some(error) = parts.error {
+            return Err(error);
         }
+
+        let has_name = parts.name.as_ref().is_some_and(|name| !name.is_empty());
+        let resolved = self.resolve(parts);
+
+        if let Some(name) = resolved.extra.keys().min() {
+            return Err(self.error_extra(name));
+        }
+
+        if let Some(pos) = resolved.surplus.first() {
+            return Err(if has_name {
+                NamedRoutesError::ConflictingParameter {
+                    route: self.template.raw().to_string(),
+                    parameter: pos.to_string(),
+                }
+            } else {
+                self.error_extra(&pos.to_string())
+            });
+        }
+
+        if let Some(name) = resolved.missing.first() {
+            return Err(self.error_missing(name));
+        }
+
+        if let Some(name) = resolved.misplaced_segments.first() {
+            return Err(NamedRoutesError::InvalidParts(format!(
+                "`{}` of `{}` is not a catch-all parameter and cannot take `Segments`",
+                name,
+                self.template.raw()
+            )));
+        }
+
+        if let Some(name) = self
+            .parameters()
+            .into_iter()
+            .find(|name| resolved.values.get(*name).is_some_and(|v| v.is_empty()))
+        {
+            return Err(NamedRoutesError::EmptyParameter {
+                route: self.template.raw().to_string(),
+                parameter: name.to_string(),
+            });
+        }
+
+        Ok(self
+            .make_redirector(Some(resolved.values))
+            .with_encoded(resolved.encoded))
+    }
+
+    /// Returns the names of the path's parameters in the order they appear.
+    /// The `*` of catch-all parameters is not part of the name
+    pub fn parameters(&self) -> Vec<&str> {
+        self.template
+            .parameters()
+            .iter()
+            .map(String::as_str)
+            .collect()
     }
 
     pub fn has_parts(&self) -> bool {
-        self.has_parts
+        !self.template.parameters().is_empty()
     }
 
+    /// Returns the HTTP methods the route was registered for.
+    /// The list is empty when they are not known, e.g. for routes added with `name_route`
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Returns true when the route accepts the method. GET routes accept HEAD and
+    /// routes with unknown methods accept every method
+    pub fn accepts(&self, method: &Method) -> bool {
+        accepts_method(&self.methods, method)
+    }
+
+    /// Returns the path as it was registered, e.g. `/user/{id}`
+    pub fn template(&self) -> &str {
+        self.template.raw()
+    }
+
+    /// Redirects to the route, a route that cannot be followed with a GET is
+    /// only logged, see `Redirector::redirect`
     pub fn redirect<T: IntoResponse>(&self, response: T) -> Response<Body> {
         self.make_redirector(None).redirect(response)
     }
 
+    /// Returns `MethodNotAllowed` when a GET cannot follow the redirect, see
+    /// `Redirector::try_redirect_as`
+    pub fn try_redirect<T: IntoResponse>(
+        &self,
+        response: T,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.make_redirector(None).try_redirect(response)
+    }
+
+    pub fn redirect_as<T: IntoResponse>(&self, kind: RedirectKind, response: T) -> Response<Body> {
+        self.make_redirector(None).redirect_as(kind, response)
+    }
+
+    pub fn try_redirect_as<T: IntoResponse>(
+        &self,
+        kind: RedirectKind,
+        response: T,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.make_redirector(None).try_redirect_as(kind, response)
+    }
+
+    /// Redirects with see other/303
+    pub fn see_other(&self) -> Response<Body> {
+        self.make_redirector(None).see_other()
+    }
+
+    /// Redirects with temporary redirect/307
+    pub fn temporary(&self) -> Response<Body> {
+        self.make_redirector(None).temporary()
+    }
+
+    /// Redirects with moved permanently/301
+    pub fn permanent(&self) -> Response<Body> {
+        self.make_redirector(None).permanent()
+    }
+
+    /// Redirects with permanent redirect/308
+    pub fn permanent_redirect(&self) -> Response<Body> {
+        self.make_redirector(None).permanent_redirect()
+    }
+
     pub fn redirect_t<T>(&self, body: T) -> Response<T> {
         self.make_redirector(None).redirect_t(body)
     }
 
+    pub fn try_redirect_t<T>(&self, body: T) -> Result<Response<T>, NamedRoutesError> {
+        self.make_redirector(None).try_redirect_t(body)
+    }
+
     pub fn redirect_meta(&self) -> String {
         self.make_redirector(None).redirect_meta()
     }
@@ -116,18 +371,137 @@ impl RoutePath {
         self.make_redirector(None)
     }
 
+    pub(crate) fn add_methods(&mut self, methods: &[Method]) {
+        for method in methods {
+            if !self.methods.contains(method) {
+                self.methods.push(method.clone());
+            }
+        }
+    }
+
+    pub(crate) fn parsed(&self) -> &Template {
+        &self.template
+    }
+
+    pub(crate) fn with_settings(mut self, settings: RouteSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
     fn make_redirector(&self, parts: Option<HashMap<String, String>>) -> Redirector {
-        Redirector::new(&self.raw, parts)
+        Redirector::new(self.template.clone(), parts)
+            .with_settings(self.settings.clone())
+            .with_methods(self.methods.clone())
+    }
+
+    /// Pairs the values with the path's parameters. Named values are used
+    /// first, then the k-th positional value fills the k-th parameter that
+    /// has not been named
+    fn resolve(&self, parts: PartsValue) -> ResolvedParts {
+        let encoded_names = parts.encoded_names;
+        let mut named: HashMap<String, (String, bool)> = parts
+            .name
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| {
+                let encoded = encoded_names.contains(&name);
+                let name = name
+                    .trim_start_matches('{')
+                    .trim_end_matches('}')
+                    .trim_start_matches('*')
+                    .to_string();
+                (name, (value, encoded))
+            })
+            .collect();
+        let mut pos = parts.pos.unwrap_or_default();
+        let encoded = parts.encoded;
+
+        let mut resolved = ResolvedParts::default();
+        let mut next = 0;
+        for name in self.parameters() {
+            if let Some((value, is_encoded)) = named.remove(name) {
+                if is_encoded {
+                    resolved.mark_encoded(name, self.template.is_catch_all(name));
+                }
+                resolved.values.insert(name.to_string(), value);
+            } else {
+                if encoded.contains(&next) {
+                    resolved.mark_encoded(name, self.template.is_catch_all(name));
+                }
+                match pos.remove(&next) {
+                    Some(value) => resolved.values.insert(name.to_string(), value),
+                    None => {
+                        resolved.missing.push(name.to_string());
+                        None
+                    }
+                };
+                next += 1;
+            }
+        }
+
+        resolved.extra = named
+            .into_iter()
+            .map(|(name, (value, _))| (name, value))
+            .collect();
+        resolved.surplus = pos.into_keys().collect();
+        resolved
+    }
+
+    fn error_missing(&self, parameter: &str) -> NamedRoutesError {
+        NamedRoutesError::MissingParameter {
+            route: self.template.raw().to_string(),
+            parameter: parameter.to_string(),
+        }
+    }
+
+    fn error_extra(&self, parameter: &str) -> NamedRoutesError {
+        NamedRoutesError::ExtraParameter {
+            route: self.template.raw().to_string(),
+            parameter: parameter.to_string(),
+        }
+    }
+}
+
+pub(crate) fn accepts_method(methods: &[Method], method: &Method) -> bool {
+    methods.is_empty()
+        || methods.contains(method)
+        || (method == Method::HEAD && methods.contains(&Method::GET))
+}
+
+/// Values paired with a path's parameters by `RoutePath::resolve`
+#[derive(Debug, Default)]
+struct ResolvedParts {
+    values: HashMap<String, String>,
+    /// Parameters that were not given a value
+    missing: Vec<String>,
+    /// Named values that do not match a parameter
+    extra: HashMap<String, String>,
+    /// Positions that are left over once every parameter is filled
+    surplus: Vec<usize>,
+    /// Parameters whose values are already percent-encoded
+    encoded: HashSet<String>,
+    /// Parameters that are not catch-all but were given `Segments`
+    misplaced_segments: Vec<String>,
+}
+
+impl ResolvedParts {
+    /// Only catch-all parameters keep their `/` separators, any other
+    /// parameter given `Segments` has its value encoded again
+    fn mark_encoded(&mut self, name: &str, catch_all: bool) {
+        if catch_all {
+            self.encoded.insert(name.to_string());
+        } else {
+            self.misplaced_segments.push(name.to_string());
+        }
     }
 }
 
 impl From<&str> for RoutePath {
     fn from(value: &str) -> Self {
         Self {
-            raw: value.to_string(),
-            has_parts: value
-                .split('/')
-                .any(|v| v.starts_with('{') && v.ends_with('}')),
+            template: Template::parse(value),
+            methods: Vec::new(),
+            settings: RouteSettings::default(),
         }
     }
 }
@@ -138,6 +512,26 @@ impl From<String> for RoutePath {
     }
 }
 
+impl From<()> for PartsValue {
+    fn from(_: ()) -> Self {
+        PartsValue {
+            pos: Some(BTreeMap::new()),
+            name: None,
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
+        }
+    }
+}
+
+impl From<Segments> for PartsValue {
+    fn from(value: Segments) -> Self {
+        let mut parts = PartsValue::from(value.encode());
+        parts.encoded.insert(0);
+        parts
+    }
+}
+
 impl From<&str> for PartsValue {
     fn from(value: &str) -> Self {
         let mut map = BTreeMap::new();
@@ -145,10 +539,19 @@ impl From<&str> for PartsValue {
         PartsValue {
             pos: Some(map),
             name: None,
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
         }
     }
 }
 
+impl From<&String> for PartsValue {
+    fn from(value: &String) -> Self {
+        value.as_str().into()
+    }
+}
+
 impl From<String> for PartsValue {
     fn from(value: String) -> Self {
         let mut map = BTreeMap::new();
@@ -156,6 +559,9 @@ impl From<String> for PartsValue {
         PartsValue {
             pos: Some(map),
             name: None,
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
         }
     }
 }
@@ -170,174 +576,103 @@ impl<K: ToString, V: ToString> From<HashMap<K, V>> for PartsValue {
                     .map(|(k, v)| (k.to_string(), v.to_string()))
                     .collect(),
             ),
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
         }
     }
 }
 
-impl<V: ToString> From<Vec<V>> for PartsValue {
-    fn from(value: Vec<V>) -> Self {
-        let mut map = BTreeMap::new();
-
-        for (pos, value) in value.into_iter().enumerate() {
-            map.insert(pos, value.to_string());
-        }
-
-        Self {
-            pos: Some(map),
-            name: None,
-        }
-    }
-}
-
-// TODO: Implement a macro to handle tuple with 7 values
-impl<V: ToString> From<(V,)> for PartsValue {
-    fn from(value: (V,)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
+impl<K: ToString> From<HashMap<K, Segments>> for PartsValue {
+    fn from(value: HashMap<K, Segments>) -> Self {
+        let name: HashMap<String, String> = value
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.encode()))
+            .collect();
 
         Self {
-            pos: Some(map),
-            name: None,
-        }
-    }
-}
-
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V)> for PartsValue {
-    fn from(value: (V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-
-        Self {
-            pos: Some(map),
-            name: None,
-        }
-    }
-}
-
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V)> for PartsValue {
-    fn from(value: (V, V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-
-        Self {
-            pos: Some(map),
-            name: None,
+            encoded_names: name.keys().cloned().collect(),
+            pos: None,
+            name: Some(name),
+            error: None,
+            encoded: BTreeSet::new(),
         }
     }
 }
 
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V, V)> for PartsValue {
-    fn from(value: (V, V, V, V)) -> Self {
+impl<V: ToString> From<Vec<V>> for PartsValue {
+    fn from(value: Vec<V>) -> Self {
         let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-        map.insert(3, value.3.to_string());
 
-        Self {
-            pos: Some(map),
-            name: None,
+        for (pos, value) in value.into_iter().enumerate() {
+            map.insert(pos, value.to_string());
         }
-    }
-}
-
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V, V, V)> for PartsValue {
-    fn from(value: (V, V, V, V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-        map.insert(3, value.3.to_string());
-        map.insert(4, value.4.to_string());
 
         Self {
             pos: Some(map),
             name: None,
+            error: None,
+            encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
         }
     }
 }
 
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V, V, V, V)> for PartsValue {
-    fn from(value: (V, V, V, V, V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-        map.insert(3, value.3.to_string());
-        map.insert(4, value.4.to_string());
-        map.insert(5, value.5.to_string());
-
-        Self {
-            pos: Some(map),
-            name: None,
-        }
-    }
-}
-
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V, V, V, V, V)> for PartsValue {
-    fn from(value: (V, V, V, V, V, V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-        map.insert(3, value.3.to_string());
-        map.insert(4, value.4.to_string());
-        map.insert(5, value.5.to_string());
-        map.insert(6, value.6.to_string());
-
-        Self {
-            pos: Some(map),
-            name: None,
+/// Implements `From` for tuples, each element can be of a different type
+macro_rules! impl_from_tuple {
+    ($($ty:ident $idx:tt),+) => {
+        impl<$($ty: ToString),+> From<($($ty,)+)> for PartsValue {
+            fn from(value: ($($ty,)+)) -> Self {
+                let mut map = BTreeMap::new();
+                $(map.insert($idx, value.$idx.to_string());)+
+
+                Self {
+                    pos: Some(map),
+                    name: None,
+                    error: None,
+                    encoded: BTreeSet::new(),
+            encoded_names: BTreeSet::new(),
+                }
+            }
         }
-    }
+    };
 }
 
-// TODO: Remove when proc_macro is implemented
-impl<V: ToString> From<(V, V, V, V, V, V, V, V)> for PartsValue {
-    fn from(value: (V, V, V, V, V, V, V, V)) -> Self {
-        let mut map = BTreeMap::new();
-        map.insert(0, value.0.to_string());
-        map.insert(1, value.1.to_string());
-        map.insert(2, value.2.to_string());
-        map.insert(3, value.3.to_string());
-        map.insert(4, value.4.to_string());
-        map.insert(5, value.5.to_string());
-        map.insert(6, value.6.to_string());
-        map.insert(7, value.7.to_string());
-
-        Self {
-            pos: Some(map),
-            name: None,
-        }
-    }
-}
+impl_from_tuple!(A 0);
+impl_from_tuple!(A 0, B 1);
+impl_from_tuple!(A 0, B 1, C 2);
+impl_from_tuple!(A 0, B 1, C 2, D 3);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14);
+impl_from_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15);
 
 #[cfg(test)]
 mod test {
     use std::collections::{BTreeMap, HashMap};
 
-    use super::{PartsValue, RoutePath};
+    use super::{PartsValue, RoutePath, Segments};
+    use crate::NamedRoutesError;
 
     #[test]
     fn test_string_to_route_path() {
         let path = RoutePath::from("/a/{b}");
 
-        assert_eq!(path.has_parts(), true);
+        assert!(path.has_parts());
     }
 
     #[test]
     fn test_string_to_part_values() {
         let value = PartsValue::from("one");
-        assert_eq!(value.pos.is_some(), true);
+        assert!(value.pos.is_some());
         assert_eq!(value.pos.unwrap().get(&0).cloned(), Some("one".to_string()));
     }
 
@@ -351,6 +686,25 @@ mod test {
         assert_eq!(value.pos.unwrap().get(&1).cloned(), Some("2".to_string()));
     }
 
+    #[test]
+    fn test_mixed_tuple_to_part_values() {
+        let path = RoutePath::from("/users/{id}/posts/{slug}/{draft}");
+
+        assert_eq!(
+            path.with((42u64, "hello", true)).path(),
+            "/users/42/posts/hello/true"
+        );
+
+        let value = PartsValue::from((
+            1, "b", 'c', 4u8, 5.5, 6i64, "g", 8, 9, 10, 11, 12, 13, 14, 15, "last",
+        ));
+        assert_eq!(value.pos.as_ref().map(|pos| pos.len()), Some(16));
+        assert_eq!(
+            value.pos.unwrap().get(&15).cloned(),
+            Some("last".to_string())
+        );
+    }
+
     #[test]
     fn test_vec_to_part_values() {
         let value = PartsValue::from(vec![100, 200]);
@@ -369,8 +723,8 @@ mod test {
 
         let value = PartsValue::from(map);
 
-        assert_eq!(value.pos.is_none(), true);
-        assert_eq!(value.name.is_some(), true);
+        assert!(value.pos.is_none());
+        assert!(value.name.is_some());
 
         assert_eq!(
             value.name.as_ref().unwrap().get("a").cloned(),
@@ -403,6 +757,9 @@ mod test {
         let right_pos = PartsValue {
             pos: Some(right),
             name: None,
+            error: None,
+            encoded: Default::default(),
+            encoded_names: Default::default(),
         };
 
         let pos = left_pos.merge(&right_pos);
@@ -435,4 +792,183 @@ mod test {
             Some("y".to_string())
         );
     }
+
+    #[test]
+    fn test_route_path_parameters() {
+        let path = RoutePath::from("/user/{id}/post/{post}/{id}");
+
+        assert_eq!(path.parameters(), vec!["id", "post"]);
+    }
+
+    #[test]
+    fn test_lenient_with() {
+        let path = RoutePath::from("/user/{id}");
+
+        assert_eq!(path.with(()).path(), "/user/");
+        assert_eq!(path.with(HashMap::<&str, &str>::new()).path(), "/user/{id}");
+    }
+
+    #[test]
+    fn test_strict_with() {
+        let path = RoutePath::from("/user/{id}/post/{post}");
+
+        assert_eq!(path.try_with((1, 2)).unwrap().path(), "/user/1/post/2");
+
+        let mut map = HashMap::new();
+        map.insert("id", "1");
+        map.insert("post", "2");
+        assert_eq!(path.try_with(map).unwrap().path(), "/user/1/post/2");
+
+        assert_eq!(
+            path.try_with(()).err(),
+            Some(NamedRoutesError::MissingParameter {
+                route: "/user/{id}/post/{post}".to_string(),
+                parameter: "id".to_string()
+            })
+        );
+        assert_eq!(
+            path.try_with((1, 2, 3)).err(),
+            Some(NamedRoutesError::ExtraParameter {
+                route: "/user/{id}/post/{post}".to_string(),
+                parameter: "2".to_string()
+            })
+        );
+
+        let mut map = HashMap::new();
+        map.insert("id", "1");
+        map.insert("post", "2");
+        map.insert("page", "3");
+        assert_eq!(
+            path.try_with(map).err(),
+            Some(NamedRoutesError::ExtraParameter {
+                route: "/user/{id}/post/{post}".to_string(),
+                parameter: "page".to_string()
+            })
+        );
+
+        assert_eq!(
+            path.try_with(("1", "")).err(),
+            Some(NamedRoutesError::EmptyParameter {
+                route: "/user/{id}/post/{post}".to_string(),
+                parameter: "post".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_catch_all_parameters() {
+        let path = RoutePath::from("/files/{bucket}/{*path}");
+        assert!(path.has_parts());
+        assert_eq!(path.parameters(), vec!["bucket", "path"]);
+
+        assert_eq!(
+            path.with(("public", "docs/a b.txt")).path(),
+            "/files/public/docs/a%20b.txt"
+        );
+        assert_eq!(
+            path.try_with(("public", "docs/a b.txt")).unwrap().path(),
+            "/files/public/docs/a%20b.txt"
+        );
+
+        let mut map = HashMap::new();
+        map.insert("bucket", "public".to_string());
+        map.insert("path", "docs/a b.txt".to_string());
+        assert_eq!(
+            path.with(map.clone()).path(),
+            "/files/public/docs/a%20b.txt"
+        );
+        assert_eq!(
+            path.try_with(map).unwrap().path(),
+            "/files/public/docs/a%20b.txt"
+        );
+
+        let mut map = HashMap::new();
+        map.insert("bucket", "public");
+        map.insert("*path", "docs");
+        assert_eq!(path.try_with(map).unwrap().path(), "/files/public/docs");
+    }
+
+    #[test]
+    fn test_segments_are_encoded_one_by_one() {
+        let path = RoutePath::from("/files/{*path}");
+        let segments = Segments::from(vec!["a/b", "c d", "50%"]);
+
+        assert_eq!(
+            path.with(segments.clone()).path(),
+            "/files/a%2Fb/c%20d/50%25"
+        );
+        assert_eq!(
+            path.try_with(segments.clone()).unwrap().path(),
+            "/files/a%2Fb/c%20d/50%25"
+        );
+
+        let path = RoutePath::from("/files/{bucket}/{*path}");
+        let mut named = HashMap::new();
+        named.insert("bucket", "a/b");
+        let parts = PartsValue::from(segments).merge(&PartsValue::from(named));
+        assert_eq!(
+            path.try_with(parts).unwrap().path(),
+            "/files/a%2Fb/a%2Fb/c%20d/50%25"
+        );
+    }
+
+    #[test]
+    fn test_named_segments_are_encoded_one_by_one() {
+        let path = RoutePath::from("/files/{*path}");
+        let named = HashMap::from([("path", Segments::from(vec!["a/b", "c"]))]);
+
+        assert_eq!(path.with(named.clone()).path(), "/files/a%2Fb/c");
+        assert_eq!(path.try_with(named).unwrap().path(), "/files/a%2Fb/c");
+
+        let named = HashMap::from([("{*path}", Segments::from(vec!["a b", "c"]))]);
+        assert_eq!(path.with(named).path(), "/files/a%20b/c");
+
+        let parts = PartsValue::from(HashMap::from([("path", Segments::from(vec!["a", "b"]))]))
+            .merge(&PartsValue::from(HashMap::from([("path", "c/d")])));
+        assert_eq!(path.with(parts).path(), "/files/c/d");
+    }
+
+    #[test]
+    fn test_segments_stay_in_catch_all_parameters() {
+        let path = RoutePath::from("/users/{id}");
+        let segments = Segments::from(vec!["a", "b"]);
+
+        assert_eq!(path.with(segments.clone()).path(), "/users/a%2Fb");
+        assert!(matches!(
+            path.try_with(segments),
+            Err(NamedRoutesError::InvalidParts(_))
+        ));
+        assert!(matches!(
+            path.try_with(HashMap::from([("id", Segments::from(vec!["a", "b"]))])),
+            Err(NamedRoutesError::InvalidParts(_))
+        ));
+    }
+
+    #[test]
+    fn test_named_and_positional_parts() {
+        let path = RoutePath::from("/users/{user_id}/posts/{post_id}/{tab}");
+
+        let mut named = HashMap::new();
+        named.insert("post_id", 9);
+        let parts = PartsValue::from((1, "comments")).merge(&PartsValue::from(named));
+
+        assert_eq!(path.with(parts.clone()).path(), "/users/1/posts/9/comments");
+        assert_eq!(
+            path.try_with(parts).unwrap().path(),
+            "/users/1/posts/9/comments"
+        );
+
+        let mut named = HashMap::new();
+        named.insert("post_id", 9);
+        let parts = PartsValue::from((1, 2, "comments")).merge(&PartsValue::from(named));
+
+        assert_eq!(path.with(parts.clone()).path(), "/users/1/posts/9/2");
+        assert_eq!(
+            path.try_with(parts).err(),
+            Some(NamedRoutesError::ConflictingParameter {
+                route: "/users/{user_id}/posts/{post_id}/{tab}".to_string(),
+                parameter: "2".to_string(),
+            })
+        );
+    }
 }
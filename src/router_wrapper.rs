@@ -1,16 +1,20 @@
 use std::{convert::Infallible, future::Future};
 
 use axum::{
-    extract::{Request, State},
+    extract::{MatchedPath, Request, State},
     handler::Handler,
+    http::Method,
     middleware::{from_fn, from_fn_with_state, Next},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, head, options, patch, post, put, trace, MethodRouter, Route},
-    Router,
+    Extension, Router,
 };
 use tower::{Layer, Service};
 
-use crate::NamedRoutesRepo;
+use crate::{
+    current_route::RouteName, flash::clear_read_flash, signed_url::verify_signature,
+    DuplicatePolicy, DuplicateRoute, NamedRoutesRepo, NamedRoutesService, RouteMeta, RouteRegistry,
+};
 
 /// Axum Router Wrapper
 /// Instead of using Axum's default Router to register routes
@@ -30,6 +34,9 @@ use crate::NamedRoutesRepo;
 pub struct RouterWrapper<S = ()> {
     router: Router<S>,
     name_repo: NamedRoutesRepo,
+    last_name: Option<String>,
+    /// The qualified names registered through this instance and the ones merged into it
+    names: Vec<String>,
 }
 
 impl<S: Clone + Send + Sync + 'static> Default for RouterWrapper<S> {
@@ -37,6 +44,8 @@ impl<S: Clone + Send + Sync + 'static> Default for RouterWrapper<S> {
         Self {
             router: Router::new(),
             name_repo: Default::default(),
+            last_name: None,
+            names: Vec::new(),
         }
     }
 }
@@ -50,6 +59,48 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         Self {
             router: Router::new(),
             name_repo: NamedRoutesRepo::new(prefix),
+            last_name: None,
+            names: Vec::new(),
+        }
+    }
+
+    /// Creates a new instance that registers its names in the given registry
+    /// instead of the process wide one
+    pub fn with_registry(registry: RouteRegistry) -> Self {
+        Self {
+            router: Router::new(),
+            name_repo: NamedRoutesRepo::with_registry(&registry, None),
+            last_name: None,
+            names: Vec::new(),
+        }
+    }
+
+    /// Returns the registry the names are registered in
+    pub fn registry(&self) -> &RouteRegistry {
+        self.name_repo.registry()
+    }
+
+    /// Returns a service that resolves names against this instance's registry
+    pub fn service(&self) -> NamedRoutesService {
+        self.registry().service()
+    }
+
+    /// A new instance that shares this instance's registry and name prefix
+    fn child(&self, prefix: Option<&str>) -> Self {
+        self.child_named(prefix, "")
+    }
+
+    /// A new instance that shares this instance's registry. `name_prefix` is
+    /// appended to this instance's name prefix
+    fn child_named(&self, prefix: Option<&str>, name_prefix: &str) -> Self {
+        let name_prefix = self.name_repo.build_child_name_prefix(name_prefix);
+
+        Self {
+            router: Router::new(),
+            name_repo: NamedRoutesRepo::with_registry(self.registry(), prefix)
+                .with_name_prefix(&name_prefix),
+            last_name: None,
+            names: Vec::new(),
         }
     }
 
@@ -59,7 +110,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, delete(handler), name)
+        self.name_route_methods(path, delete(handler), name, &[Method::DELETE])
     }
 
     /// Register a DELETE handler with no name
@@ -77,7 +128,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, get(handler), name)
+        self.name_route_methods(path, get(handler), name, &[Method::GET])
     }
 
     /// Register a GET handler with no name
@@ -95,7 +146,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, head(handler), name)
+        self.name_route_methods(path, head(handler), name, &[Method::HEAD])
     }
 
     /// Register a HEAD handler with no name
@@ -113,7 +164,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, options(handler), name)
+        self.name_route_methods(path, options(handler), name, &[Method::OPTIONS])
     }
 
     /// Register a OPTIONS handler with no name
@@ -131,7 +182,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, patch(handler), name)
+        self.name_route_methods(path, patch(handler), name, &[Method::PATCH])
     }
 
     /// Register a PATCH handler with no name
@@ -149,7 +200,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, post(handler), name)
+        self.name_route_methods(path, post(handler), name, &[Method::POST])
     }
 
     /// Register a POST handler with no name
@@ -167,7 +218,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, put(handler), name)
+        self.name_route_methods(path, put(handler), name, &[Method::PUT])
     }
 
     /// Register a PUT handler with no name
@@ -185,7 +236,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(path, trace(handler), name)
+        self.name_route_methods(path, trace(handler), name, &[Method::TRACE])
     }
 
     /// Register a TRACE handler with no name
@@ -204,7 +255,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         H: Handler<T, S>,
         T: 'static,
     {
-        self.name_route(
+        self.name_route_methods(
             path,
             get(handler.clone())
                 .post(handler.clone())
@@ -214,6 +265,15 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
                 .options(handler.clone())
                 .trace(handler.clone()),
             name,
+            &[
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::DELETE,
+                Method::PATCH,
+                Method::OPTIONS,
+                Method::TRACE,
+            ],
         )
     }
 
@@ -238,8 +298,12 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
             return self;
         }
         let list = self.build_verb_list(verbs, handler);
+        let methods: Vec<Method> = verbs
+            .iter()
+            .filter_map(|verb| verb_method(&verb.to_string()))
+            .collect();
 
-        self.name_route(path, list, name)
+        self.name_route_methods(path, list, name, &methods)
     }
 
     /// Register a route handler that handles one or more HTTP verbs:
@@ -252,18 +316,63 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         self.any_of(verbs, path, handler, path)
     }
 
+    /// Register a route handler. The wrapper's registry is added to the
+    /// request's extensions so that extractors resolve names against it, and
+    /// flash messages read by the `Flash` extractor are cleared
     pub fn route(mut self, path: &str, handler: MethodRouter<S>) -> Self {
-        self.router = self.router.route(path, handler);
+        let registry = self.registry().clone();
+        self.router = self.router.route(
+            path,
+            handler
+                .layer::<_, Infallible>(from_fn(clear_read_flash))
+                .layer(from_fn_with_state(registry, provide_registry)),
+        );
         self
     }
 
+    /// Register a named route. The route's methods are not recorded,
+    /// see `RoutePath::methods`
     pub fn name_route(self, path: &str, handler: MethodRouter<S>, name: &str) -> Self {
         self.name_repo.register(name, path);
-        self.route(path, handler)
+        self.tagged_route(path, handler, name)
+    }
+
+    fn name_route_methods(
+        self,
+        path: &str,
+        handler: MethodRouter<S>,
+        name: &str,
+        methods: &[Method],
+    ) -> Self {
+        self.name_repo.register_methods(name, path, methods);
+        self.tagged_route(path, handler, name)
+    }
+
+    /// Adds the route with its name in the request's extensions, see `CurrentRoute`
+    fn tagged_route(mut self, path: &str, handler: MethodRouter<S>, name: &str) -> Self {
+        let name = self.name_repo.qualified_name(name);
+        self.names.push(name.clone());
+        self.last_name = Some(name.clone());
+        self.route(path, handler.layer(Extension(RouteName(name))))
+    }
+
+    /// Sets the metadata of the last registered name, replacing any metadata it had.
+    /// Does nothing when no name has been registered yet
+    pub fn meta(self, meta: RouteMeta) -> Self {
+        if let Some(name) = &self.last_name {
+            self.registry().set_meta(name, meta);
+        }
+        self
     }
 
+    /// Adds the wrapper's routes. Their names are copied into this instance's
+    /// registry, which is also the one their extractors resolve names against
     pub fn merge(mut self, wrapper: Self) -> Self {
-        self.router = self.router.merge(wrapper.into_router());
+        self.registry()
+            .import(wrapper.registry(), &wrapper.names, None);
+        self.names.extend(wrapper.names.iter().cloned());
+        let router = self.adopt(wrapper);
+        self.router = self.router.merge(router);
         self
     }
 
@@ -272,28 +381,80 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
     where
         C: FnMut(Self) -> Self,
     {
-        self.merge(callback(Self::new()))
+        let child = self.child(None);
+        self.merge(callback(child))
     }
 
     pub fn nest(mut self, path: &str, wrapper: Self) -> Self {
+        if !self.registry().same_as(wrapper.registry()) {
+            let prefix = self.name_repo.build_child_prefix(path);
+            self.registry().import(
+                wrapper.registry(),
+                &wrapper.names,
+                Some(prefix.trim_end_matches('/')),
+            );
+        }
+        self.names.extend(wrapper.names.iter().cloned());
+
+        let router = self.adopt(wrapper);
         if path == "/" {
-            self.router = self.router.merge(wrapper.into_router());
+            self.router = self.router.merge(router);
         } else {
-            self.router = self.router.nest(path, wrapper.into_router());
+            self.router = self.router.nest(path, router);
         }
         self
     }
 
+    /// Returns the wrapper's router with this instance's registry added to its
+    /// requests. A route only adds its own registry when none is there yet
+    fn adopt(&self, wrapper: Self) -> Router<S> {
+        wrapper.into_router().layer(from_fn_with_state(
+            self.registry().clone(),
+            provide_registry,
+        ))
+    }
+
     /// An new instance of this struct will be passed to the provided callback
     pub fn nest_given<C>(self, path: &str, mut callback: C) -> Self
     where
         C: FnMut(Self) -> Self,
     {
         let full_path = self.name_repo.build_child_prefix(path);
-        self.nest(
-            path,
-            callback(Self::new_with_prefix(Some(full_path.as_str()))),
-        )
+        let child = self.child(Some(full_path.as_str()));
+        self.nest(path, callback(child))
+    }
+
+    /// Like `nest_given` but the names registered by the callback are prefixed
+    /// with `name_prefix`, e.g. `users.index` becomes `admin.users.index`
+    ///
+    /// ```rust
+    ///  use named_routes_axum::{RouteRegistry, RouterWrapper};
+    ///
+    /// let registry = RouteRegistry::new();
+    /// let _ = RouterWrapper::<()>::with_registry(registry.clone())
+    ///     .nest_given_named("/admin", "admin.", |router| {
+    ///         router.get("/users", || async { "users" }, "users.index")
+    ///     });
+    ///
+    /// assert_eq!(registry.service().get_path("admin.users.index"), Some("/admin/users".to_string()));
+    /// ```
+    pub fn nest_given_named<C>(self, path: &str, name_prefix: &str, mut callback: C) -> Self
+    where
+        C: FnMut(Self) -> Self,
+    {
+        let full_path = self.name_repo.build_child_prefix(path);
+        let child = self.child_named(Some(full_path.as_str()), name_prefix);
+        self.nest(path, callback(child))
+    }
+
+    /// Like `merge_given` but the names registered by the callback are prefixed
+    /// with `name_prefix`
+    pub fn merge_given_named<C>(self, name_prefix: &str, mut callback: C) -> Self
+    where
+        C: FnMut(Self) -> Self,
+    {
+        let child = self.child_named(None, name_prefix);
+        self.merge(callback(child))
     }
 
     pub fn middleware<F, Fut, Out>(mut self, f: F) -> Self
@@ -304,7 +465,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
     {
         self.router = self.router.route_layer(from_fn(f));
 
-        self
+        self.with_route_extensions()
     }
 
     /// Register tower's layer service
@@ -330,7 +491,7 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         <L::Service as Service<Request>>::Future: Send + 'static,
     {
         self.router = self.router.route_layer(layer);
-        self
+        self.with_route_extensions()
     }
 
     /// Provides the inner Axum's Router to the callback
@@ -352,14 +513,45 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
     {
         self.router = self.router.route_layer(from_fn_with_state(state, f));
 
+        self.with_route_extensions()
+    }
+
+    /// Adds the registry and the route's name to the requests before the route
+    /// layers run, as the ones added by `route` are only there for the handler
+    fn with_route_extensions(mut self) -> Self {
+        let registry = self.registry().clone();
+        self.router = self
+            .router
+            .route_layer(from_fn_with_state(registry, provide_route));
         self
     }
 
+    /// Rejects requests to the routes registered so far unless their URL
+    /// was signed with `NamedRoutesService::signed_path` and has not expired
+    pub fn require_signature(self) -> Self {
+        let service = self.service();
+        self.middleware_with_state(verify_signature, service)
+    }
+
     /// Returns the Axum Router instance
     pub fn into_router(self) -> Router<S> {
         self.router
     }
 
+    /// Returns the Axum Router instance or, when the registry's duplicate
+    /// policy is `DuplicatePolicy::Error`, the names that collided since the
+    /// last call. Reported duplicates are cleared from the registry
+    pub fn try_into_router(self) -> Result<Router<S>, Vec<DuplicateRoute>> {
+        if self.registry().duplicate_policy() == DuplicatePolicy::Error {
+            let duplicates = self.registry().take_duplicates();
+            if !duplicates.is_empty() {
+                return Err(duplicates);
+            }
+        }
+
+        Ok(self.router)
+    }
+
     pub fn build_verb_list<H, T, V>(&self, verbs: &[V], handler: H) -> MethodRouter<S>
     where
         H: Handler<T, S>,
@@ -369,14 +561,14 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
         let mut list: MethodRouter<S> = MethodRouter::new();
 
         for entry in verbs {
-            list = match entry.to_string().trim().to_ascii_uppercase().as_str() {
-                "GET" => list.get(handler.clone()),
-                "POST" => list.post(handler.clone()),
-                "PUT" => list.put(handler.clone()),
-                "DELETE" => list.delete(handler.clone()),
-                "PATCH" => list.patch(handler.clone()),
-                "OPTION" | "OPTIONS" => list.options(handler.clone()),
-                "TRACE" => list.trace(handler.clone()),
+            list = match verb_method(&entry.to_string()) {
+                Some(Method::GET) => list.get(handler.clone()),
+                Some(Method::POST) => list.post(handler.clone()),
+                Some(Method::PUT) => list.put(handler.clone()),
+                Some(Method::DELETE) => list.delete(handler.clone()),
+                Some(Method::PATCH) => list.patch(handler.clone()),
+                Some(Method::OPTIONS) => list.options(handler.clone()),
+                Some(Method::TRACE) => list.trace(handler.clone()),
                 _ => list,
             };
         }
@@ -385,11 +577,66 @@ impl<S: Clone + Send + Sync + 'static> RouterWrapper<S> {
     }
 }
 
+/// Adds the registry to the request's extensions unless an outer wrapper did
+async fn provide_registry(
+    State(registry): State<RouteRegistry>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if request.extensions().get::<RouteRegistry>().is_none() {
+        request.extensions_mut().insert(registry);
+    }
+
+    next.run(request).await
+}
+
+/// Like `provide_registry` but also names the route from axum's `MatchedPath`
+async fn provide_route(
+    State(registry): State<RouteRegistry>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let registry = request
+        .extensions()
+        .get::<RouteRegistry>()
+        .cloned()
+        .unwrap_or(registry);
+
+    if request.extensions().get::<RouteName>().is_none() {
+        let name = request
+            .extensions()
+            .get::<MatchedPath>()
+            .and_then(|matched| registry.name_of(matched.as_str(), request.method()));
+        if let Some(name) = name {
+            request.extensions_mut().insert(RouteName(name));
+        }
+    }
+    request.extensions_mut().insert(registry);
+
+    next.run(request).await
+}
+
+/// Returns the method of the verbs accepted by `any_of`
+fn verb_method(verb: &str) -> Option<Method> {
+    match verb.trim().to_ascii_uppercase().as_str() {
+        "GET" => Some(Method::GET),
+        "POST" => Some(Method::POST),
+        "PUT" => Some(Method::PUT),
+        "DELETE" => Some(Method::DELETE),
+        "PATCH" => Some(Method::PATCH),
+        "OPTION" | "OPTIONS" => Some(Method::OPTIONS),
+        "TRACE" => Some(Method::TRACE),
+        _ => None,
+    }
+}
+
 impl<S> From<Router<S>> for RouterWrapper<S> {
     fn from(router: Router<S>) -> Self {
         Self {
             router,
             name_repo: Default::default(),
+            last_name: None,
+            names: Vec::new(),
         }
     }
 }
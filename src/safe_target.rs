@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use axum::http::Uri;
+
+use crate::BaseUrl;
+
+/// A user supplied URL or path that belongs to one of the registered routes.
+/// Returned by `NamedRoutesService::resolve_safe_target`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafeTarget {
+    /// The path and query inside the application, e.g. `/users/42?tab=posts`
+    pub path: String,
+    /// The name of the matched route
+    pub name: String,
+    /// The percent-decoded parameter values of the matched route
+    pub params: HashMap<String, String>,
+}
+
+/// Returns true when the absolute URL is on the base URL's origin or on one of
+/// the allowed hosts. An allowed host without a port accepts any port
+pub(crate) fn is_allowed_origin(uri: &Uri, base_url: Option<&BaseUrl>, hosts: &[String]) -> bool {
+    let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+        return false;
+    };
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return false;
+    }
+
+    base_url.is_some_and(|base_url| base_url.same_origin(uri))
+        || hosts.iter().any(|host| {
+            if host.contains(':') {
+                host.eq_ignore_ascii_case(authority.as_str())
+            } else {
+                host.eq_ignore_ascii_case(authority.host())
+            }
+        })
+}
+
+/// Returns the path and query of a target that is not an absolute URL.
+/// Protocol relative paths are refused as browsers treat them as another host
+pub(crate) fn relative_path(target: &str) -> Option<&str> {
+    (target.starts_with('/') && !target.starts_with("//")).then_some(target)
+}
@@ -0,0 +1,571 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{
+    ser::{self, Impossible, SerializeMap, SerializeStruct},
+    Serialize, Serializer,
+};
+
+use crate::NamedRoutesError;
+
+/// Wraps a struct or a map so that its fields can be used as named parts.
+/// Field names are matched with the path's `{placeholders}`
+///
+/// ```rust
+///  use named_routes_axum::{RoutePath, Serialized};
+///
+/// #[derive(serde::Serialize)]
+/// struct Params {
+///     user_id: u64,
+/// }
+///
+/// let route = RoutePath::from("/user/{user_id}");
+///
+/// assert_eq!(route.with(Serialized(&Params { user_id: 1 })).path(), "/user/1");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Serialized<'a, T: ?Sized>(pub &'a T);
+
+/// Turns a struct or a map into parameter names and values
+pub(crate) fn to_named_parts<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<HashMap<String, String>, NamedRoutesError> {
+    value
+        .serialize(PartsSerializer)
+        .map_err(|error| NamedRoutesError::InvalidParts(error.0))
+}
+
+#[derive(Debug)]
+pub(crate) struct PartsError(String);
+
+impl Display for PartsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PartsError {}
+
+impl ser::Error for PartsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, PartsError> {
+    Err(PartsError(format!(
+        "expected a struct or a map, found {}",
+        what
+    )))
+}
+
+fn nested<T>(what: &str) -> Result<T, PartsError> {
+    Err(PartsError(format!("{} values are not supported", what)))
+}
+
+struct PartsSerializer;
+
+type Parts = HashMap<String, String>;
+
+impl Serializer for PartsSerializer {
+    type Ok = Parts;
+    type Error = PartsError;
+    type SerializeSeq = Impossible<Parts, PartsError>;
+    type SerializeTuple = Impossible<Parts, PartsError>;
+    type SerializeTupleStruct = Impossible<Parts, PartsError>;
+    type SerializeTupleVariant = Impossible<Parts, PartsError>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = Impossible<Parts, PartsError>;
+
+    fn serialize_bool(self, _: bool) -> Result<Parts, PartsError> {
+        unsupported("a bool")
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<Parts, PartsError> {
+        unsupported("a number")
+    }
+
+    fn serialize_char(self, _: char) -> Result<Parts, PartsError> {
+        unsupported("a char")
+    }
+
+    fn serialize_str(self, _: &str) -> Result<Parts, PartsError> {
+        unsupported("a string")
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Parts, PartsError> {
+        unsupported("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Parts, PartsError> {
+        unsupported("none")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Parts, PartsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Parts, PartsError> {
+        unsupported("a unit")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Parts, PartsError> {
+        unsupported("a unit struct")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<Parts, PartsError> {
+        unsupported("an enum")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Parts, PartsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Parts, PartsError> {
+        unsupported("an enum")
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, PartsError> {
+        unsupported("a sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, PartsError> {
+        unsupported("a tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, PartsError> {
+        unsupported("a tuple struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, PartsError> {
+        unsupported("an enum")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, PartsError> {
+        Ok(MapSerializer {
+            parts: HashMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, PartsError> {
+        Ok(StructSerializer {
+            parts: HashMap::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, PartsError> {
+        unsupported("an enum")
+    }
+}
+
+struct StructSerializer {
+    parts: Parts,
+}
+
+impl SerializeStruct for StructSerializer {
+    type Ok = Parts;
+    type Error = PartsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), PartsError> {
+        let value = value
+            .serialize(ValueSerializer)
+            .map_err(|error| PartsError(format!("field `{}`: {}", key, error)))?;
+
+        if let Some(value) = value {
+            self.parts.insert(key.to_string(), value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Parts, PartsError> {
+        Ok(self.parts)
+    }
+}
+
+struct MapSerializer {
+    parts: Parts,
+    key: Option<String>,
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Parts;
+    type Error = PartsError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), PartsError> {
+        match key.serialize(ValueSerializer)? {
+            Some(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            None => Err(PartsError(
+                "map keys must be strings or numbers".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), PartsError> {
+        let key = self.key.take().unwrap_or_default();
+        let value = value
+            .serialize(ValueSerializer)
+            .map_err(|error| PartsError(format!("key `{}`: {}", key, error)))?;
+
+        if let Some(value) = value {
+            self.parts.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Parts, PartsError> {
+        Ok(self.parts)
+    }
+}
+
+/// Serializes a single parameter value. `None` means the parameter is left out
+struct ValueSerializer;
+
+type Value = Option<String>;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = PartsError;
+    type SerializeSeq = Impossible<Value, PartsError>;
+    type SerializeTuple = Impossible<Value, PartsError>;
+    type SerializeTupleStruct = Impossible<Value, PartsError>;
+    type SerializeTupleVariant = Impossible<Value, PartsError>;
+    type SerializeMap = Impossible<Value, PartsError>;
+    type SerializeStruct = Impossible<Value, PartsError>;
+    type SerializeStructVariant = Impossible<Value, PartsError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, PartsError> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Value, PartsError> {
+        nested("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Value, PartsError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, PartsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, PartsError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, PartsError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, PartsError> {
+        Ok(Some(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, PartsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, PartsError> {
+        nested("enum")
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, PartsError> {
+        nested("sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, PartsError> {
+        nested("tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, PartsError> {
+        nested("tuple struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, PartsError> {
+        nested("enum")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, PartsError> {
+        nested("map")
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, PartsError> {
+        nested("struct")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, PartsError> {
+        nested("enum")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use super::Serialized;
+    use crate::{NamedRoutesError, PartsValue, RoutePath};
+
+    #[derive(Serialize)]
+    struct UserParams {
+        user_id: u64,
+        post_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        page: Option<u32>,
+    }
+
+    #[derive(Serialize)]
+    struct Nested {
+        user: UserParams,
+    }
+
+    #[test]
+    fn test_struct_to_part_values() {
+        let path = RoutePath::from("/user/{user_id}/post/{post_id}");
+        let params = UserParams {
+            user_id: 1,
+            post_id: "hello world".to_string(),
+            page: None,
+        };
+
+        assert_eq!(
+            path.with(Serialized(&params)).path(),
+            "/user/1/post/hello%20world"
+        );
+        assert_eq!(
+            path.try_with(PartsValue::try_from_serialize(&params).unwrap())
+                .unwrap()
+                .path(),
+            "/user/1/post/hello%20world"
+        );
+    }
+
+    #[test]
+    fn test_map_to_part_values() {
+        let path = RoutePath::from("/user/{user_id}");
+        let mut map = BTreeMap::new();
+        map.insert("user_id", 7);
+
+        assert_eq!(path.try_with(Serialized(&map)).unwrap().path(), "/user/7");
+    }
+
+    #[test]
+    fn test_nested_values_are_rejected() {
+        let path = RoutePath::from("/user/{user_id}");
+        let params = Nested {
+            user: UserParams {
+                user_id: 1,
+                post_id: "2".to_string(),
+                page: None,
+            },
+        };
+
+        assert_eq!(
+            path.try_with(Serialized(&params)).err(),
+            Some(NamedRoutesError::InvalidParts(
+                "field `user`: struct values are not supported".to_string()
+            ))
+        );
+        assert_eq!(
+            path.try_with(Serialized("7")).err(),
+            Some(NamedRoutesError::InvalidParts(
+                "expected a struct or a map, found a string".to_string()
+            ))
+        );
+        assert_eq!(
+            PartsValue::try_from_serialize(&vec![1, 2]).err(),
+            Some(NamedRoutesError::InvalidParts(
+                "expected a struct or a map, found a sequence".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a struct or a map, found a string")]
+    fn test_with_does_not_drop_conversion_errors() {
+        let _ = RoutePath::from("/user/{id}").with(Serialized(&String::from("42")));
+    }
+
+    #[test]
+    fn test_references_are_not_serialized() {
+        let path = RoutePath::from("/user/{id}");
+
+        let id = String::from("42");
+
+        assert_eq!(path.with(&id).path(), "/user/42");
+        assert_eq!(path.with(id).path(), "/user/42");
+    }
+}
@@ -1,22 +1,23 @@
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use std::{collections::HashMap, time::SystemTime};
+
+use axum::http::{Method, Uri};
 
-use crate::{PartsValue, RepoInner, RoutePath, NAME_ROUTES_REPO};
+use crate::{
+    route_match::RouteMatch,
+    safe_target::{is_allowed_origin, relative_path, SafeTarget},
+    signed_url::{sign_path, unix_timestamp, verify_uri},
+    BaseUrl, NamedRoutesError, PartsValue, QueryParams, Redirector, RouteMeta, RoutePath,
+    RouteRegistry,
+};
 
 #[derive(Debug, Clone)]
 pub struct NamedRoutesService {
-    repo: RepoInner,
+    repo: RouteRegistry,
 }
 
 impl Default for NamedRoutesService {
     fn default() -> Self {
-        Self {
-            repo: Arc::clone(
-                NAME_ROUTES_REPO.get_or_init(|| Arc::new(RwLock::new(HashMap::new()))),
-            ),
-        }
+        Self::with_registry(&RouteRegistry::global())
     }
 }
 
@@ -25,24 +26,137 @@ impl NamedRoutesService {
         Self::default()
     }
 
-    pub fn get(&self, name: &str) -> Option<RoutePath> {
-        if let Ok(read) = self.repo.read() {
-            read.get(name).cloned()
-        } else {
-            None
+    /// Creates a service that resolves names against the given registry
+    pub fn with_registry(registry: &RouteRegistry) -> Self {
+        Self {
+            repo: registry.clone(),
         }
     }
 
+    /// Returns the registry this service resolves names against
+    pub fn registry(&self) -> &RouteRegistry {
+        &self.repo
+    }
+
+    pub fn get(&self, name: &str) -> Option<RoutePath> {
+        self.try_get(name).ok()
+    }
+
+    pub fn try_get(&self, name: &str) -> Result<RoutePath, NamedRoutesError> {
+        let read = self
+            .repo
+            .routes()
+            .read()
+            .map_err(|_| NamedRoutesError::PoisonedLock)?;
+
+        read.get(name)
+            .cloned()
+            .map(|route| route.with_settings(self.repo.settings()))
+            .ok_or_else(|| NamedRoutesError::UnknownRoute(name.to_string()))
+    }
+
     pub fn get_path(&self, name: &str) -> Option<String> {
         self.get(name).map(|v| v.redirector().path())
     }
 
+    pub fn try_get_path(&self, name: &str) -> Result<String, NamedRoutesError> {
+        self.try_get(name).map(|v| v.redirector().path())
+    }
+
     pub fn get_path_with<V: Into<PartsValue>>(&self, name: &str, parts: V) -> Option<String> {
         self.get(name).map(|v| v.with(parts).path())
     }
 
+    /// Returns the route's path with its parameters validated as done by `RoutePath::try_with`
+    pub fn try_get_path_with<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<String, NamedRoutesError> {
+        self.try_get(name)?.try_with(parts).map(|v| v.path())
+    }
+
+    pub fn get_path_with_query<V: Into<PartsValue>, Q: Into<QueryParams>>(
+        &self,
+        name: &str,
+        parts: V,
+        query: Q,
+    ) -> Option<String> {
+        self.get(name)
+            .map(|v| v.with(parts).with_query(query).path())
+    }
+
+    /// Returns the route's path and query string with its parameters validated as done by `RoutePath::try_with`
+    pub fn try_get_path_with_query<V: Into<PartsValue>, Q: Into<QueryParams>>(
+        &self,
+        name: &str,
+        parts: V,
+        query: Q,
+    ) -> Result<String, NamedRoutesError> {
+        self.try_get(name)?
+            .try_with(parts)
+            .map(|v| v.with_query(query).path())
+    }
+
+    /// Returns the route's absolute URL built with the registry's base URL.
+    /// The parts are validated as done by `RoutePath::try_with`
+    pub fn get_url<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<Uri, NamedRoutesError> {
+        self.try_get(name)?.try_with(parts)?.url()
+    }
+
+    /// Returns the route's absolute URL built with the given base URL
+    pub fn get_url_with_base<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+        base_url: &BaseUrl,
+    ) -> Result<Uri, NamedRoutesError> {
+        self.try_get(name)?.try_with(parts)?.url_with_base(base_url)
+    }
+
+    /// Returns the route's path with an `expires` and a `signature` query parameter.
+    /// The parts are validated as done by `RoutePath::try_with`
+    pub fn signed_path<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+        expires_at: SystemTime,
+    ) -> Result<String, NamedRoutesError> {
+        let path = self
+            .try_get(name)?
+            .try_with(parts)?
+            .with_query(("expires", unix_timestamp(expires_at)))
+            .path();
+
+        sign_path(&self.repo.signing_keys(), &path)
+    }
+
+    /// Returns the signed path joined with the registry's base URL
+    pub fn signed_url<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+        expires_at: SystemTime,
+    ) -> Result<Uri, NamedRoutesError> {
+        let base_url = self
+            .repo
+            .base_url()
+            .ok_or(NamedRoutesError::MissingBaseUrl)?;
+
+        base_url.join(&self.signed_path(name, parts, expires_at)?)
+    }
+
+    /// Checks that the URL was signed with one of the registry's keys and has not expired
+    pub fn verify_signed(&self, uri: &Uri) -> Result<(), NamedRoutesError> {
+        verify_uri(&self.repo.signing_keys(), uri)
+    }
+
     pub fn has(&self, name: &str) -> bool {
-        if let Ok(read) = self.repo.read() {
+        if let Ok(read) = self.repo.routes().read() {
             read.contains_key(name)
         } else {
             false
@@ -50,33 +164,213 @@ impl NamedRoutesService {
     }
 
     pub fn all(&self) -> HashMap<String, RoutePath> {
-        if let Ok(read) = self.repo.read() {
-            read.clone()
+        self.repo.all()
+    }
+
+    /// Returns the redirector to `target` when it is a safe target, see
+    /// `resolve_safe_target`, and to the default route otherwise.
+    /// The redirect goes to the path that was checked, it is not rebuilt from
+    /// the route's decoded parameters
+    pub fn intended_redirector(
+        &self,
+        target: Option<&str>,
+        default_route: &str,
+    ) -> Result<Redirector, NamedRoutesError> {
+        let redirector = target
+            .and_then(|target| self.resolve_safe_target(target))
+            .and_then(|target| {
+                let route = self.get(&target.name)?;
+                Some(route.redirector().with_literal_path(&target.path))
+            });
+
+        match redirector {
+            Some(redirector) => Ok(redirector),
+            None => Ok(self.try_get(default_route)?.redirector()),
+        }
+    }
+
+    /// Returns the route the path belongs to and its parameter values.
+    /// The query string is ignored. When several templates match, the one with
+    /// the most literal text wins
+    ///
+    /// ```rust
+    ///  use named_routes_axum::RouteRegistry;
+    ///
+    /// let registry = RouteRegistry::new();
+    /// registry.register("post", "/users/{user_id}/posts/{post_id}");
+    ///
+    /// let found = registry.service().recognize("/users/42/posts/7").unwrap();
+    ///
+    /// assert_eq!(found.name, "post");
+    /// assert_eq!(found.param("user_id"), Some("42"));
+    /// ```
+    pub fn recognize(&self, path: &str) -> Option<RouteMatch> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        self.match_path(path, None)
+    }
+
+    /// Like `recognize` but only matches the routes that accept the method
+    pub fn recognize_method(&self, path: &str, method: &Method) -> Option<RouteMatch> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        self.match_path(path, Some(method))
+    }
+
+    /// Returns the routes whose names are in the namespace. The names in the
+    /// `admin` namespace start with `admin.`
+    pub fn in_namespace(&self, namespace: &str) -> HashMap<String, RoutePath> {
+        let prefix = format!("{}.", namespace.trim_end_matches('.'));
+
+        self.all()
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .map(|(name, route)| (name, route.with_settings(self.repo.settings())))
+            .collect()
+    }
+
+    /// Returns the metadata set with `RouterWrapper::meta` or `RouteRegistry::set_meta`
+    pub fn meta(&self, name: &str) -> Option<RouteMeta> {
+        self.repo.meta(name)
+    }
+
+    /// Returns the routes whose metadata has the tag
+    pub fn routes_tagged(&self, tag: &str) -> HashMap<String, RoutePath> {
+        self.repo
+            .names_tagged(tag)
+            .into_iter()
+            .filter_map(|name| self.get(&name).map(|route| (name, route)))
+            .collect()
+    }
+
+    /// Returns the HTTP methods the route was registered for
+    pub fn methods(&self, name: &str) -> Option<Vec<Method>> {
+        self.get(name).map(|route| route.methods().to_vec())
+    }
+
+    /// Returns the target when it matches one of the registered routes and is either
+    /// a relative path or an absolute URL on the base URL's host or an allowed host.
+    ///
+    /// ```rust
+    ///  use named_routes_axum::RouteRegistry;
+    ///
+    /// let registry = RouteRegistry::new();
+    /// registry.register("user", "/users/{id}");
+    /// registry.set_allowed_hosts(["example.com"]);
+    ///
+    /// let service = registry.service();
+    /// let target = service.resolve_safe_target("https://example.com/users/42?tab=posts").unwrap();
+    ///
+    /// assert_eq!(target.path, "/users/42?tab=posts");
+    /// assert_eq!(target.name, "user");
+    /// assert!(service.resolve_safe_target("https://evil.com/users/42").is_none());
+    /// ```
+    pub fn resolve_safe_target(&self, target: &str) -> Option<SafeTarget> {
+        if target.contains('\\') || target.chars().any(char::is_control) {
+            return None;
+        }
+
+        let base_url = self.repo.base_url();
+        let absolute = target
+            .parse::<Uri>()
+            .ok()
+            .filter(|uri| uri.scheme().is_some() || uri.authority().is_some());
+        let (path, query) = if let Some(uri) = absolute {
+            if !is_allowed_origin(&uri, base_url.as_ref(), &self.repo.allowed_hosts()) {
+                return None;
+            }
+
+            (uri.path().to_string(), uri.query().map(|q| q.to_string()))
         } else {
-            HashMap::new()
+            let target = relative_path(target)?;
+            match target.split_once('?') {
+                Some((path, query)) => (path.to_string(), Some(query.to_string())),
+                None => (target.to_string(), None),
+            }
+        };
+        let path = path.split('#').next().unwrap_or_default();
+        let path = base_url
+            .as_ref()
+            .and_then(|base_url| base_url.strip_prefix(path))
+            .unwrap_or(path);
+        let path = relative_path(path)?;
+
+        let RouteMatch { name, params, .. } = self.match_path(path, Some(&Method::GET))?;
+        let path = match query {
+            Some(query) => format!("{}?{}", path, query.split('#').next().unwrap_or_default()),
+            None => path.to_string(),
+        };
+
+        Some(SafeTarget { path, name, params })
+    }
+
+    /// Returns the route the path belongs to. When several templates match,
+    /// the one with the most literal text wins
+    pub(crate) fn match_path(&self, path: &str, method: Option<&Method>) -> Option<RouteMatch> {
+        let read = self.repo.routes().read().ok()?;
+
+        read.iter()
+            .filter(|(_, route)| method.is_none_or(|method| route.accepts(method)))
+            .filter_map(|(name, route)| {
+                route
+                    .parsed()
+                    .matches(path)
+                    .map(|params| (name, route, params))
+            })
+            .max_by(|a, b| {
+                a.1.parsed()
+                    .literal_len()
+                    .cmp(&b.1.parsed().literal_len())
+                    .then_with(|| b.0.cmp(a.0))
+            })
+            .map(|(name, route, params)| RouteMatch {
+                name: name.clone(),
+                params,
+                methods: route.methods().to_vec(),
+            })
+    }
+
+    /// Returns a redirector to a path and query of one of the registered routes
+    pub(crate) fn redirector_for(&self, path_and_query: &str) -> Option<Redirector> {
+        let (path, query) = match path_and_query.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (path_and_query, None),
+        };
+
+        let found = self.match_path(path, Some(&Method::GET))?;
+        let mut redirector = self.try_get(&found.name).ok()?.with(found.params);
+        if let Some(query) = query {
+            let pairs: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect();
+            redirector = redirector.with_query(pairs);
         }
+
+        Some(redirector)
     }
 }
 
 #[allow(unused_imports)]
 #[cfg(test)]
 mod test {
-    use crate::{NamedRoutesRepo, NamedRoutesService};
+    use crate::{NamedRoutesError, NamedRoutesRepo, NamedRoutesService, RouteRegistry};
 
-    const HOME_URL: (&'static str, &'static str) = ("home", "/");
-    const URL2: (&'static str, &'static str) = ("url_2", "/first");
-    const URL3: (&'static str, &'static str) = ("url_3", "/second");
-    const URL4: (&'static str, &'static str) = ("url_4", "/four/{path1}/{path2}");
-    const URL5: (&'static str, &'static str) = ("url_5", "/four/{path1}/{path2}/user/{path3}");
+    const HOME_URL: (&str, &str) = ("home", "/");
+    const URL2: (&str, &str) = ("url_2", "/first");
+    const URL3: (&str, &str) = ("url_3", "/second");
+    const URL4: (&str, &str) = ("url_4", "/four/{path1}/{path2}");
+    const URL5: (&str, &str) = ("url_5", "/four/{path1}/{path2}/user/{path3}");
 
     #[allow(unused)]
     fn setup_name_repo() {
-        NamedRoutesRepo::default()
-            .register(HOME_URL.0, &HOME_URL.1)
-            .register(URL2.0, &URL2.1)
-            .register(URL3.0, &URL3.1)
-            .register(URL4.0, &URL4.1)
-            .register(URL5.0, &URL5.1);
+        register_routes(&RouteRegistry::global());
+    }
+
+    fn register_routes(registry: &RouteRegistry) {
+        NamedRoutesRepo::with_registry(registry, None)
+            .register(HOME_URL.0, HOME_URL.1)
+            .register(URL2.0, URL2.1)
+            .register(URL3.0, URL3.1)
+            .register(URL4.0, URL4.1)
+            .register(URL5.0, URL5.1);
     }
 
     #[test]
@@ -121,10 +415,33 @@ mod test {
         assert_eq!(name_service.get_path("second-foo"), None);
     }
 
+    #[test]
+    fn test_routes_in_namespace() {
+        let registry = RouteRegistry::new();
+        let admin =
+            NamedRoutesRepo::with_registry(&registry, Some("/admin")).with_name_prefix("admin.");
+        admin.register("dashboard", "/");
+        NamedRoutesRepo::with_registry(&registry, Some("/admin/users"))
+            .with_name_prefix(&admin.build_child_name_prefix("users."))
+            .register("index", "/");
+        register_routes(&registry);
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        assert_eq!(
+            name_service.get_path("admin.users.index"),
+            Some("/admin/users/".to_string())
+        );
+        assert_eq!(name_service.in_namespace("admin").len(), 2);
+        assert_eq!(name_service.in_namespace("admin.").len(), 2);
+        assert_eq!(name_service.in_namespace("admin.users").len(), 1);
+        assert!(name_service.in_namespace("adm").is_empty());
+    }
+
     #[test]
     fn test_getting_all_the_names() {
-        setup_name_repo();
-        let name_service = NamedRoutesService::new();
+        let registry = RouteRegistry::new();
+        register_routes(&registry);
+        let name_service = NamedRoutesService::with_registry(&registry);
         let all = name_service.all();
 
         // success
@@ -148,4 +465,170 @@ mod test {
         // fail test
         assert_eq!(name_service2.get_path("home2"), None);
     }
+
+    #[test]
+    fn test_unknown_route_error() {
+        setup_name_repo();
+        let name_service = NamedRoutesService::new();
+
+        assert_eq!(
+            name_service.try_get_path("fake1"),
+            Err(NamedRoutesError::UnknownRoute("fake1".to_string()))
+        );
+        assert_eq!(
+            name_service.try_get_path_with(URL4.0, vec![1, 2]),
+            Ok("/four/1/2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_getting_url_with_query() {
+        setup_name_repo();
+        let name_service = NamedRoutesService::new();
+
+        assert_eq!(
+            name_service.get_path_with_query(URL4.0, (1, 2), [("page", 3)]),
+            Some("/four/1/2?page=3".to_string())
+        );
+        assert_eq!(
+            name_service.try_get_path_with_query(URL2.0, (), [("q", "a b")]),
+            Ok("/first?q=a+b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_getting_absolute_url() {
+        let registry = RouteRegistry::new();
+        register_routes(&registry);
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        assert_eq!(
+            name_service.get_url(URL4.0, (1, 2)),
+            Err(NamedRoutesError::MissingBaseUrl)
+        );
+
+        registry.set_base_url("https://example.com/app".parse().unwrap());
+        assert_eq!(
+            name_service.get_url(URL4.0, (1, 2)).unwrap().to_string(),
+            "https://example.com/app/four/1/2"
+        );
+        assert_eq!(
+            name_service
+                .get_url_with_base(URL2.0, (), &"http://localhost:3000".parse().unwrap())
+                .unwrap()
+                .to_string(),
+            "http://localhost:3000/first"
+        );
+    }
+
+    #[test]
+    fn test_resolve_safe_target() {
+        let registry = RouteRegistry::new();
+        register_routes(&registry);
+        registry
+            .set_base_url("https://example.com/app".parse().unwrap())
+            .set_allowed_hosts(["www.example.com"]);
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        let target = name_service
+            .resolve_safe_target("/four/1/a%20b?x=1")
+            .unwrap();
+        assert_eq!(target.path, "/four/1/a%20b?x=1");
+        assert_eq!(target.name, URL4.0);
+        assert_eq!(target.params.get("path2").map(String::as_str), Some("a b"));
+
+        assert_eq!(
+            name_service
+                .resolve_safe_target("https://example.com/app/first")
+                .map(|target| target.path),
+            Some("/first".to_string())
+        );
+        assert_eq!(
+            name_service
+                .resolve_safe_target("http://WWW.example.com:8080/first#top")
+                .map(|target| target.name),
+            Some(URL2.0.to_string())
+        );
+
+        for target in [
+            "https://evil.com/first",
+            "//evil.com/first",
+            "/\\evil.com",
+            "javascript:alert(1)",
+            "/unknown",
+            "first",
+            "http://example.com/app/first",
+        ] {
+            assert_eq!(name_service.resolve_safe_target(target), None, "{}", target);
+        }
+    }
+
+    #[test]
+    fn test_resolve_safe_target_behind_a_catch_all() {
+        let registry = RouteRegistry::new();
+        registry.register("spa", "/{*path}");
+        registry.set_base_url("https://example.com/app".parse().unwrap());
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        for target in [
+            "https://example.com//evil.com",
+            "https://example.com/app//evil.com",
+            "/app//evil.com",
+        ] {
+            assert_eq!(name_service.resolve_safe_target(target), None, "{}", target);
+        }
+
+        assert_eq!(
+            name_service
+                .resolve_safe_target("/search?q=https://rust-lang.org")
+                .map(|target| target.path),
+            Some("/search?q=https://rust-lang.org".to_string())
+        );
+    }
+
+    #[test]
+    fn test_intended_redirector_refuses_protocol_relative_targets() {
+        let registry = RouteRegistry::new();
+        registry.register("home", "/").register("spa", "/{*path}");
+        registry.set_base_url("https://example.com".parse().unwrap());
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        let path = |target| {
+            name_service
+                .intended_redirector(Some(target), "home")
+                .unwrap()
+                .path()
+        };
+
+        assert_eq!(path("https://example.com//evil.com"), "/");
+        assert_eq!(path("https://example.com/docs/intro"), "/docs/intro");
+        assert_eq!(path("/%2Fevil.com"), "/%2Fevil.com");
+        assert_eq!(path("/docs/%7Bid%7D?q=a%20b"), "/docs/%7Bid%7D?q=a%20b");
+    }
+
+    #[test]
+    fn test_recognize_path() {
+        let registry = RouteRegistry::new();
+        register_routes(&registry);
+        registry.register("four_new", "/four/new/{path2}");
+        let name_service = NamedRoutesService::with_registry(&registry);
+
+        let found = name_service.recognize("/four/1/2/user/3?page=1").unwrap();
+        assert_eq!(found.name, URL5.0);
+        assert_eq!(found.param("path3"), Some("3"));
+
+        assert_eq!(
+            name_service
+                .recognize("/four/new/2")
+                .map(|found| found.name),
+            Some("four_new".to_string())
+        );
+        assert_eq!(
+            name_service
+                .recognize("/four/old/2")
+                .map(|found| found.name),
+            Some(URL4.0.to_string())
+        );
+        assert_eq!(name_service.recognize("/five"), None);
+    }
 }
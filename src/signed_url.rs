@@ -0,0 +1,309 @@
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{FromRequestParts, OriginalUri, Request, State},
+    http::{request::Parts, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{NamedRoutesError, NamedRoutesService, RouteRegistry};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The keys used to sign URLs. The first key signs, every key is accepted when verifying
+#[derive(Clone, Default)]
+pub(crate) struct SigningKeys(Arc<Vec<Vec<u8>>>);
+
+impl SigningKeys {
+    pub(crate) fn new<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<[u8]>,
+    {
+        Self(Arc::new(
+            keys.into_iter().map(|k| k.as_ref().to_vec()).collect(),
+        ))
+    }
+
+    /// Returns the hex encoded signature of `data` made with the first key
+    pub(crate) fn sign(&self, data: &str) -> Result<String, NamedRoutesError> {
+        let key = self.0.first().ok_or(NamedRoutesError::MissingSigningKey)?;
+        let signature = mac(key, data).finalize().into_bytes();
+
+        Ok(signature.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Returns true when any of the keys produced the hex encoded `signature`
+    pub(crate) fn verify(&self, data: &str, signature: &str) -> bool {
+        let Some(signature) = decode_hex(signature) else {
+            return false;
+        };
+
+        self.0
+            .iter()
+            .any(|key| mac(key, data).verify_slice(&signature).is_ok())
+    }
+}
+
+impl Debug for SigningKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SigningKeys([redacted; {}])", self.0.len())
+    }
+}
+
+/// Extractor that rejects the request with a 403 response when the URL's
+/// signature is invalid or has expired.
+///
+/// ```rust
+///  use named_routes_axum::ValidSignature;
+///
+/// async fn verify_email(_: ValidSignature) -> &'static str {
+///     "Your email address has been verified"
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ValidSignature;
+
+impl<S: Send + Sync> FromRequestParts<S> for ValidSignature {
+    type Rejection = NamedRoutesError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let service = RouteRegistry::from_parts(parts).service();
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|uri| &uri.0)
+            .unwrap_or(&parts.uri);
+
+        service.verify_signed(uri).map(|_| Self)
+    }
+}
+
+pub(crate) async fn verify_signature(
+    State(service): State<NamedRoutesService>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| &uri.0)
+        .unwrap_or(request.uri());
+
+    match service.verify_signed(uri) {
+        Ok(_) => next.run(request).await,
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Signs `path`, which must already contain the `expires` query parameter
+pub(crate) fn sign_path(keys: &SigningKeys, path: &str) -> Result<String, NamedRoutesError> {
+    Ok(format!("{}&signature={}", path, keys.sign(path)?))
+}
+
+/// Checks the signature and the expiry of a signed URL
+pub(crate) fn verify_uri(keys: &SigningKeys, uri: &Uri) -> Result<(), NamedRoutesError> {
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let (data, signature) = path
+        .rsplit_once("&signature=")
+        .ok_or(NamedRoutesError::InvalidSignature)?;
+
+    if !keys.verify(data, signature) {
+        return Err(NamedRoutesError::InvalidSignature);
+    }
+
+    let expires = data
+        .split_once('?')
+        .and_then(|(_, query)| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "expires")
+                .and_then(|(_, value)| value.parse::<u64>().ok())
+        })
+        .ok_or(NamedRoutesError::InvalidSignature)?;
+
+    if unix_timestamp(SystemTime::now()) > expires {
+        return Err(NamedRoutesError::ExpiredSignature);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn mac(key: &[u8], data: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, SystemTime};
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::SigningKeys;
+    use crate::{
+        test_support::registry_with, NamedRoutesError, RouteRegistry, RouterWrapper, ValidSignature,
+    };
+
+    fn signing_registry() -> RouteRegistry {
+        let registry = registry_with(&[("unsubscribe", "/unsubscribe/{id}")]);
+        registry.set_signing_keys(["secret"]);
+        registry
+    }
+
+    fn in_an_hour() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(3600)
+    }
+
+    #[test]
+    fn test_signing_keys_are_not_printed() {
+        let keys = SigningKeys::new(["secret", "old"]);
+
+        assert_eq!(format!("{:?}", keys), "SigningKeys([redacted; 2])");
+    }
+
+    #[test]
+    fn test_signed_path_is_verified() {
+        let service = signing_registry().service();
+        let path = service
+            .signed_path("unsubscribe", (1,), in_an_hour())
+            .unwrap();
+
+        assert!(path.starts_with("/unsubscribe/1?expires="));
+        assert_eq!(service.verify_signed(&path.parse().unwrap()), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_path_is_rejected() {
+        let service = signing_registry().service();
+        let path = service
+            .signed_path("unsubscribe", (1,), in_an_hour())
+            .unwrap();
+        let tampered = path.replace("/unsubscribe/1", "/unsubscribe/2");
+
+        assert_eq!(
+            service.verify_signed(&tampered.parse().unwrap()),
+            Err(NamedRoutesError::InvalidSignature)
+        );
+        assert_eq!(
+            service.verify_signed(&"/unsubscribe/1".parse().unwrap()),
+            Err(NamedRoutesError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_expired_path_is_rejected() {
+        let service = signing_registry().service();
+        let past = SystemTime::now() - Duration::from_secs(60);
+        let path = service.signed_path("unsubscribe", (1,), past).unwrap();
+
+        assert_eq!(
+            service.verify_signed(&path.parse().unwrap()),
+            Err(NamedRoutesError::ExpiredSignature)
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let registry = signing_registry();
+        let service = registry.service();
+        let path = service
+            .signed_path("unsubscribe", (1,), in_an_hour())
+            .unwrap();
+
+        registry.set_signing_keys(["new-secret", "secret"]);
+        assert_eq!(service.verify_signed(&path.parse().unwrap()), Ok(()));
+
+        registry.set_signing_keys(["new-secret"]);
+        assert_eq!(
+            service.verify_signed(&path.parse().unwrap()),
+            Err(NamedRoutesError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_signing_without_a_key() {
+        let service = registry_with(&[("home", "/")]).service();
+
+        assert_eq!(
+            service.signed_path("home", (), in_an_hour()),
+            Err(NamedRoutesError::MissingSigningKey)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signature_is_required() {
+        let registry = signing_registry();
+        let app = RouterWrapper::<()>::with_registry(registry.clone())
+            .get(
+                "/unsubscribe/{id}",
+                || async { "unsubscribed" },
+                "unsubscribe",
+            )
+            .require_signature()
+            .get(
+                "/download",
+                |_: ValidSignature| async { "file" },
+                "download",
+            )
+            .into_router();
+
+        let signed = registry
+            .service()
+            .signed_path("unsubscribe", (1,), in_an_hour())
+            .unwrap();
+        let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request(&signed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request("/unsubscribe/1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.clone().oneshot(request("/download")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let signed = registry
+            .service()
+            .signed_path("download", (), in_an_hour())
+            .unwrap();
+        let response = app.oneshot(request(&signed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use percent_encoding::percent_decode_str;
+
+/// A piece of a route template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    /// Text that is copied as it is
+    Literal(String),
+    /// A `{name}` or `{*name}` parameter. `index` points into `Template::parameters`
+    Parameter { index: usize, catch_all: bool },
+}
+
+/// A route template that is parsed once, when the route is registered.
+/// Cloning is cheap so the parsed template can be shared by every `Redirector`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Template(Arc<Parsed>);
+
+#[derive(Debug, Default)]
+struct Parsed {
+    raw: String,
+    segments: Vec<Segment>,
+    parameters: Vec<String>,
+    literal_len: usize,
+}
+
+impl Template {
+    pub(crate) fn parse(raw: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut parameters: Vec<String> = Vec::new();
+        let mut literal = String::new();
+        let mut rest = raw;
+
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix("{{") {
+                literal.push('{');
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix("}}") {
+                literal.push('}');
+                rest = tail;
+            } else if let Some((name, tail)) = parameter(rest) {
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+
+                let catch_all = name.starts_with('*');
+                let name = name.trim_start_matches('*');
+                let index = match parameters.iter().position(|p| p == name) {
+                    Some(index) => index,
+                    None => {
+                        parameters.push(name.to_string());
+                        parameters.len() - 1
+                    }
+                };
+
+                segments.push(Segment::Parameter { index, catch_all });
+                rest = tail;
+            } else {
+                let mut chars = rest.chars();
+                if let Some(c) = chars.next() {
+                    literal.push(c);
+                }
+                rest = chars.as_str();
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        let literal_len = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.len(),
+                Segment::Parameter { .. } => 0,
+            })
+            .sum();
+
+        Self(Arc::new(Parsed {
+            raw: raw.to_string(),
+            segments,
+            parameters,
+            literal_len,
+        }))
+    }
+
+    /// Returns the template as it was registered
+    pub(crate) fn raw(&self) -> &str {
+        &self.0.raw
+    }
+
+    pub(crate) fn segments(&self) -> &[Segment] {
+        &self.0.segments
+    }
+
+    /// Returns the unique parameter names in the order they appear
+    pub(crate) fn parameters(&self) -> &[String] {
+        &self.0.parameters
+    }
+
+    pub(crate) fn parameter(&self, index: usize) -> &str {
+        &self.0.parameters[index]
+    }
+
+    /// Returns true when the parameter is written as `{*name}`
+    pub(crate) fn is_catch_all(&self, name: &str) -> bool {
+        self.0.segments.iter().any(|segment| {
+            matches!(segment, Segment::Parameter { index, catch_all: true } if self.parameter(*index) == name)
+        })
+    }
+
+    /// Length of the path without any of the parameter values
+    pub(crate) fn literal_len(&self) -> usize {
+        self.0.literal_len
+    }
+
+    /// Returns the parameter values when `path` matches the template.
+    /// The values are percent-decoded
+    pub(crate) fn matches(&self, path: &str) -> Option<HashMap<String, String>> {
+        let mut values = vec![None; self.parameters().len()];
+        if !self.match_segments(self.segments(), path, &mut values) {
+            return None;
+        }
+
+        Some(
+            self.parameters()
+                .iter()
+                .cloned()
+                .zip(values.into_iter().map(Option::unwrap_or_default))
+                .collect(),
+        )
+    }
+
+    fn match_segments(
+        &self,
+        segments: &[Segment],
+        path: &str,
+        values: &mut Vec<Option<String>>,
+    ) -> bool {
+        let Some((first, rest)) = segments.split_first() else {
+            return path.is_empty();
+        };
+
+        match first {
+            Segment::Literal(literal) => path
+                .strip_prefix(literal.as_str())
+                .is_some_and(|tail| self.match_segments(rest, tail, values)),
+            Segment::Parameter { index, catch_all } => {
+                let end = if *catch_all {
+                    path.len()
+                } else {
+                    path.find('/').unwrap_or(path.len())
+                };
+
+                // The longest value is tried first, a repeated parameter must have the same value
+                for len in (1..=end).rev().filter(|len| path.is_char_boundary(*len)) {
+                    let value = percent_decode_str(&path[..len])
+                        .decode_utf8_lossy()
+                        .into_owned();
+                    if values[*index].as_ref().is_some_and(|v| *v != value) {
+                        continue;
+                    }
+
+                    let previous = values[*index].replace(value);
+                    if self.match_segments(rest, &path[len..], values) {
+                        return true;
+                    }
+                    values[*index] = previous;
+                }
+
+                false
+            }
+        }
+    }
+
+    /// Writes the parameter back as it appears in the template
+    pub(crate) fn write_placeholder<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        index: usize,
+        catch_all: bool,
+    ) -> fmt::Result {
+        if catch_all {
+            write!(out, "{{*{}}}", self.parameter(index))
+        } else {
+            write!(out, "{{{}}}", self.parameter(index))
+        }
+    }
+}
+
+impl From<&str> for Template {
+    fn from(value: &str) -> Self {
+        Self::parse(value)
+    }
+}
+
+/// Returns the name of the `{parameter}` at the start of `value` and what follows it
+fn parameter(value: &str) -> Option<(&str, &str)> {
+    let value = value.strip_prefix('{')?;
+    let end = value.find('}')?;
+    let name = &value[..end];
+
+    if name.trim_start_matches('*').is_empty() || name.contains(['/', '{']) {
+        return None;
+    }
+
+    Some((name, &value[end + 1..]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Segment, Template};
+
+    #[test]
+    fn test_parse_template() {
+        let template = Template::parse("/users/{id}/files/{*path}");
+
+        assert_eq!(
+            template.segments(),
+            &[
+                Segment::Literal("/users/".to_string()),
+                Segment::Parameter {
+                    index: 0,
+                    catch_all: false
+                },
+                Segment::Literal("/files/".to_string()),
+                Segment::Parameter {
+                    index: 1,
+                    catch_all: true
+                },
+            ]
+        );
+        assert_eq!(template.parameters(), &["id", "path"]);
+        assert_eq!(template.literal_len(), 14);
+    }
+
+    #[test]
+    fn test_repeated_and_escaped_parameters() {
+        let template = Template::parse("/{id}/{id}.json/{{literal}}/{}");
+
+        assert_eq!(template.parameters(), &["id"]);
+        assert_eq!(
+            template.segments()[4],
+            Segment::Literal(".json/{literal}/{}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_match_path() {
+        let template = Template::parse("/users/{id}/files/{*path}");
+
+        let values = template.matches("/users/4%202/files/docs/a.txt").unwrap();
+        assert_eq!(values.get("id").map(String::as_str), Some("4 2"));
+        assert_eq!(values.get("path").map(String::as_str), Some("docs/a.txt"));
+
+        assert!(template.matches("/users/42/files/").is_none());
+        assert!(template.matches("/users/42").is_none());
+        assert!(Template::parse("/{id}.json")
+            .matches("/a.b.json")
+            .is_some_and(|values| values["id"] == "a.b"));
+        assert!(Template::parse("/{id}/{id}").matches("/1/2").is_none());
+    }
+}
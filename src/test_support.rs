@@ -0,0 +1,48 @@
+//! Fixtures shared by the test modules
+
+use axum::{
+    http::{header, request::Parts, Request},
+    response::Response,
+};
+
+use crate::RouteRegistry;
+
+/// Returns a new registry with the given names and paths
+pub(crate) fn registry_with(routes: &[(&str, &str)]) -> RouteRegistry {
+    let registry = RouteRegistry::new();
+    for (name, path) in routes {
+        registry.register(name, path);
+    }
+    registry
+}
+
+/// Returns the parts of a GET request that carry the registry, as added by `RouterWrapper`
+pub(crate) fn request_parts(
+    registry: &RouteRegistry,
+    uri: &str,
+    headers: &[(&str, &str)],
+) -> Parts {
+    let mut builder = Request::builder().uri(uri);
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let (mut parts, _) = builder.body(()).unwrap().into_parts();
+    parts.extensions.insert(registry.clone());
+    parts
+}
+
+/// Returns the response's `Location` header
+pub(crate) fn location(response: &Response) -> String {
+    response.headers()[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Returns the response's body
+pub(crate) async fn body_string(response: Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
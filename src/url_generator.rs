@@ -0,0 +1,504 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, uri::Authority, HeaderMap, Response, Uri},
+};
+
+use crate::{BaseUrl, NamedRoutesError, NamedRoutesService, PartsValue, Redirector, RouteRegistry};
+
+/// The proxies whose `Forwarded` and `X-Forwarded-*` headers are believed
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum TrustedProxies {
+    /// Forwarded headers are ignored
+    #[default]
+    None,
+    /// Forwarded headers are always used
+    All,
+    /// Forwarded headers are used when the peer address is in the list.
+    /// The peer address is read from axum's `ConnectInfo<SocketAddr>`.
+    /// The values are taken from the rightmost hop that is not in the list
+    List(Vec<IpAddr>),
+}
+
+impl TrustedProxies {
+    pub fn trusts(&self, peer: Option<IpAddr>) -> bool {
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::List(list) => peer.map(|ip| list.contains(&ip)).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the position, counted from the right, of the forwarded hop that
+    /// was added by the proxy closest to the client. `hops` are the `for`
+    /// addresses of the hops in the order they were added. Walking from the
+    /// right, every hop added by a trusted proxy is skipped; the hops to the
+    /// left of the first untrusted address may have been sent by the client.
+    /// `None` means every hop is trusted and the leftmost one is used
+    fn client_hop(&self, hops: &[String]) -> Option<usize> {
+        match self {
+            Self::List(list) => Some(
+                hops.iter()
+                    .rev()
+                    .position(|hop| !parse_ip(hop).is_some_and(|ip| list.contains(&ip)))
+                    .unwrap_or(hops.len().saturating_sub(1)),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Builds absolute URLs for named routes from the current request.
+///
+/// The scheme, host and path prefix are read from the `Host` header and, when the
+/// peer is a trusted proxy, from the `Forwarded`, `X-Forwarded-Proto`, `X-Forwarded-Host`
+/// and `X-Forwarded-Prefix` headers. The registry's base URL fills in anything the request
+/// does not provide.
+///
+/// ```rust
+///  use named_routes_axum::UrlGenerator;
+///
+/// async fn handler(urls: UrlGenerator) -> String {
+///     urls.url("home").map(|url| url.to_string()).unwrap_or_default()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UrlGenerator {
+    service: NamedRoutesService,
+    base_url: BaseUrl,
+}
+
+impl UrlGenerator {
+    pub fn new(service: NamedRoutesService, base_url: BaseUrl) -> Self {
+        Self { service, base_url }
+    }
+
+    /// Builds the generator from a request's parts
+    pub fn from_parts(parts: &Parts) -> Result<Self, NamedRoutesError> {
+        let registry = RouteRegistry::from_parts(parts);
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip());
+
+        let proxies = registry.trusted_proxies();
+        let base_url = request_base_url(parts, registry.base_url(), &proxies, peer)?;
+
+        Ok(Self::new(registry.service(), base_url))
+    }
+
+    pub fn base_url(&self) -> &BaseUrl {
+        &self.base_url
+    }
+
+    pub fn service(&self) -> &NamedRoutesService {
+        &self.service
+    }
+
+    /// Returns the route's redirector. Its redirects use absolute `Location` headers
+    pub fn redirector<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<Redirector, NamedRoutesError> {
+        Ok(self
+            .service
+            .try_get(name)?
+            .try_with(parts)?
+            .with_base_url(self.base_url.clone()))
+    }
+
+    pub fn url(&self, name: &str) -> Result<Uri, NamedRoutesError> {
+        self.url_with(name, ())
+    }
+
+    pub fn url_with<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<Uri, NamedRoutesError> {
+        self.redirector(name, parts)?.url()
+    }
+
+    pub fn redirect(&self, name: &str) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirect_with(name, ())
+    }
+
+    pub fn redirect_with<V: Into<PartsValue>>(
+        &self,
+        name: &str,
+        parts: V,
+    ) -> Result<Response<Body>, NamedRoutesError> {
+        self.redirector(name, parts)?.try_redirect(())
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for UrlGenerator {
+    type Rejection = NamedRoutesError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::from_parts(parts)
+    }
+}
+
+fn request_base_url(
+    parts: &Parts,
+    fallback: Option<BaseUrl>,
+    proxies: &TrustedProxies,
+    peer: Option<IpAddr>,
+) -> Result<BaseUrl, NamedRoutesError> {
+    let headers = &parts.headers;
+    let trusted = proxies.trusts(peer);
+    let forwarded = if trusted {
+        forwarded_values(headers, proxies)
+    } else {
+        (None, None)
+    };
+    let hop = proxies.client_hop(&header_values(headers, "x-forwarded-for"));
+    let forwarded_value = |name: &str| {
+        trusted
+            .then(|| pick(header_values(headers, name), hop))
+            .flatten()
+    };
+
+    let scheme = forwarded
+        .0
+        .or_else(|| forwarded_value("x-forwarded-proto"))
+        .or_else(|| parts.uri.scheme_str().map(|s| s.to_string()))
+        .or_else(|| fallback.as_ref().map(|b| b.scheme_str().to_string()))
+        .unwrap_or_else(|| "http".to_string());
+
+    let host = forwarded
+        .1
+        .or_else(|| forwarded_value("x-forwarded-host"))
+        .or_else(|| pick(header_values(headers, header::HOST.as_str()), None))
+        .or_else(|| parts.uri.authority().map(|a| a.to_string()))
+        .and_then(|host| host.parse::<Authority>().ok());
+
+    let prefix = forwarded_value("x-forwarded-prefix")
+        .or_else(|| fallback.as_ref().map(|b| b.prefix_str().to_string()))
+        .unwrap_or_default();
+
+    let base_url = match (host, fallback) {
+        (Some(host), _) => {
+            let base = BaseUrl::new(&scheme, host.host());
+            match host.port_u16() {
+                Some(port) => base.port(port),
+                None => base,
+            }
+        }
+        (None, Some(fallback)) => fallback,
+        (None, None) => return Err(NamedRoutesError::MissingBaseUrl),
+    };
+
+    Ok(base_url.prefix(&prefix))
+}
+
+/// Returns the `proto` and `host` of the RFC 7239 `Forwarded` element added by
+/// the proxy closest to the client, see `TrustedProxies::client_hop`
+fn forwarded_values(
+    headers: &HeaderMap,
+    proxies: &TrustedProxies,
+) -> (Option<String>, Option<String>) {
+    let elements: Vec<Vec<(String, String)>> = header_values(headers, header::FORWARDED.as_str())
+        .iter()
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| {
+                    (
+                        key.trim().to_ascii_lowercase(),
+                        value.trim().trim_matches('"').to_string(),
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let field = |element: &[(String, String)], name: &str| {
+        element
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    let hops: Vec<String> = elements
+        .iter()
+        .map(|element| field(element, "for").unwrap_or_default())
+        .collect();
+
+    match pick(elements, proxies.client_hop(&hops)) {
+        Some(element) => (field(&element, "proto"), field(&element, "host")),
+        None => (None, None),
+    }
+}
+
+/// Returns the value at the position counted from the right, or the leftmost
+/// value when the list is shorter or the position is `None`
+fn pick<T>(values: Vec<T>, from_right: Option<usize>) -> Option<T> {
+    let last = values.len().checked_sub(1)?;
+    let from_right = from_right.unwrap_or(last).min(last);
+    values.into_iter().nth(last - from_right)
+}
+
+/// Returns the comma separated values of every occurrence of a header
+fn header_values(headers: &HeaderMap, name: &str) -> Vec<String> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Parses a forwarded address such as `10.0.0.1`, `10.0.0.1:80` or `"[::1]:80"`
+fn parse_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim_matches('"');
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| {
+            value
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .ok()
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, SocketAddr};
+
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{header, request::Parts, Request},
+    };
+    use tower::ServiceExt;
+
+    use super::{TrustedProxies, UrlGenerator};
+    use crate::{
+        test_support::{body_string, registry_with, request_parts},
+        BaseUrl, RouteRegistry, RouterWrapper,
+    };
+
+    const ROUTES: &[(&str, &str)] = &[("user", "/user/{id}")];
+
+    /// Request parts received from the peer 10.0.0.1
+    fn parts(registry: &RouteRegistry, headers: &[(&str, &str)]) -> Parts {
+        let mut parts = request_parts(registry, "/", headers);
+        parts
+            .extensions
+            .insert(ConnectInfo("10.0.0.1:4000".parse::<SocketAddr>().unwrap()));
+        parts
+    }
+
+    #[test]
+    fn test_url_from_host_header() {
+        let registry = registry_with(ROUTES);
+        let parts = parts(&registry, &[("host", "example.com:8080")]);
+        let urls = UrlGenerator::from_parts(&parts).unwrap();
+
+        assert_eq!(
+            urls.url_with("user", (1,)).unwrap().to_string(),
+            "http://example.com:8080/user/1"
+        );
+    }
+
+    #[test]
+    fn test_forwarded_headers_are_ignored_from_untrusted_peers() {
+        let registry = registry_with(ROUTES);
+        let parts = parts(
+            &registry,
+            &[
+                ("host", "internal:3000"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-host", "evil.com"),
+            ],
+        );
+        let urls = UrlGenerator::from_parts(&parts).unwrap();
+
+        assert_eq!(
+            urls.base_url(),
+            &BaseUrl::new("http", "internal").port(3000)
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_headers() {
+        let registry = registry_with(ROUTES);
+        registry.set_trusted_proxies(TrustedProxies::List(vec!["10.0.0.1"
+            .parse::<IpAddr>()
+            .unwrap()]));
+        let parts = parts(
+            &registry,
+            &[
+                ("host", "internal:3000"),
+                ("x-forwarded-for", "203.0.113.9"),
+                ("x-forwarded-proto", "https"),
+                ("x-forwarded-host", "example.com"),
+                ("x-forwarded-prefix", "/app"),
+            ],
+        );
+        let urls = UrlGenerator::from_parts(&parts).unwrap();
+
+        assert_eq!(
+            urls.url_with("user", (1,)).unwrap().to_string(),
+            "https://example.com/app/user/1"
+        );
+
+        let response = urls.redirect_with("user", (1,)).unwrap();
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/app/user/1"
+        );
+    }
+
+    #[test]
+    fn test_spoofed_forwarded_values_are_skipped() {
+        let registry = registry_with(ROUTES);
+        registry.set_trusted_proxies(TrustedProxies::List(vec![
+            "10.0.0.1".parse::<IpAddr>().unwrap(),
+            "10.0.0.2".parse::<IpAddr>().unwrap(),
+        ]));
+        let base_url = |headers: &[(&str, &str)]| {
+            UrlGenerator::from_parts(&parts(&registry, headers))
+                .unwrap()
+                .base_url()
+                .clone()
+        };
+
+        // the client sent the leftmost values, 10.0.0.2 then 10.0.0.1 appended theirs
+        let found = base_url(&[
+            ("host", "internal:3000"),
+            ("x-forwarded-for", "6.6.6.6, 203.0.113.9, 10.0.0.2"),
+            ("x-forwarded-proto", "http, https, http"),
+            ("x-forwarded-host", "evil.com, example.com, internal"),
+        ]);
+        assert_eq!(found, BaseUrl::new("https", "example.com"));
+
+        // a single value written by the edge proxy is kept
+        let found = base_url(&[
+            ("host", "internal:3000"),
+            ("x-forwarded-for", "203.0.113.9, 10.0.0.2"),
+            ("x-forwarded-proto", "https"),
+        ]);
+        assert_eq!(found, BaseUrl::new("https", "internal").port(3000));
+
+        let forwarded =
+            "for=6.6.6.6;proto=http;host=evil.com, for=203.0.113.9;proto=https;host=example.com";
+        let found = base_url(&[("host", "internal:3000"), ("forwarded", forwarded)]);
+        assert_eq!(found, BaseUrl::new("https", "example.com"));
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let registry = registry_with(ROUTES);
+        registry.set_trusted_proxies(TrustedProxies::All);
+        let parts = parts(
+            &registry,
+            &[
+                ("host", "internal:3000"),
+                (
+                    "forwarded",
+                    "for=192.0.2.60;proto=https;host=\"example.com:8443\", for=10.0.0.1",
+                ),
+            ],
+        );
+        let urls = UrlGenerator::from_parts(&parts).unwrap();
+
+        assert_eq!(
+            urls.base_url(),
+            &BaseUrl::new("https", "example.com").port(8443)
+        );
+    }
+
+    #[test]
+    fn test_registry_base_url_is_the_fallback() {
+        let registry = registry_with(ROUTES);
+        registry.set_base_url("https://example.com/app".parse().unwrap());
+        let parts = parts(&registry, &[]);
+        let urls = UrlGenerator::from_parts(&parts).unwrap();
+
+        assert_eq!(
+            urls.url_with("user", (1,)).unwrap().to_string(),
+            "https://example.com/app/user/1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extractor_uses_the_wrapper_registry() {
+        let registry = RouteRegistry::new();
+        let app = RouterWrapper::<()>::with_registry(registry)
+            .get(
+                "/",
+                |urls: UrlGenerator| async move { urls.url("generator-home").unwrap().to_string() },
+                "generator-home",
+            )
+            .into_router();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header("host", "example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(body_string(response).await, "http://example.com/");
+    }
+
+    #[tokio::test]
+    async fn test_merged_routes_use_the_parent_registry() {
+        async fn home_url(urls: UrlGenerator) -> String {
+            urls.url("home")
+                .map(|url| url.to_string())
+                .unwrap_or_else(|error| error.to_string())
+        }
+
+        let app = RouterWrapper::<()>::with_registry(RouteRegistry::new())
+            .get("/", || async { "home" }, "home")
+            .merge(
+                RouterWrapper::with_registry(RouteRegistry::new()).get("/child", home_url, "child"),
+            )
+            .nest(
+                "/nested",
+                RouterWrapper::with_registry(RouteRegistry::new()).get(
+                    "/child",
+                    home_url,
+                    "nested.child",
+                ),
+            )
+            .into_router();
+
+        for uri in ["/child", "/nested/child"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(uri)
+                        .header("host", "example.com")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                body_string(response).await,
+                "http://example.com/",
+                "{}",
+                uri
+            );
+        }
+    }
+}